@@ -8,11 +8,15 @@
 
 use proc_macro2;
 
-use caches;
 use definition;
 
 pub struct Constructor {
     name: syn::Ident,
+    opkind: syn::Path,
+    inputs: syn::punctuated::Punctuated<syn::FnArg, Token![,]>,
+    output: syn::ReturnType,
+    // the mnemonics with their actions, in order
+    mnemonics: Vec<syn::Ident>,
     actions: Vec<syn::Ident>,
     action_impls: Vec<proc_macro2::TokenStream>,
     unknown: syn::Block,
@@ -22,33 +26,36 @@ impl Constructor {
     pub fn new(action_group: definition::ActionGroup) -> Self {
         let definition::ActionGroup {
             name,
+            opkind,
+            inputs,
+            output,
             actions,
             unknown,
         } = action_group;
-        let action_unknown = syn::Ident::new("exec_unknown", proc_macro2::Span::call_site());
-        let mut action_array: Vec<syn::Ident> = vec![action_unknown; 256];
+        let mut mnemonics = Vec::new();
+        let mut action_idents = Vec::new();
         let mut action_impls: Vec<proc_macro2::TokenStream> = Vec::new();
         for action in actions.into_iter() {
             let definition::Action { mnemonic, block } = action;
-            let mnemonic_string = &mnemonic.to_string();
-            let mut value = 0;
-            caches::OPCODE_TABLE.with(|f| {
-                value = *(*f.borrow_mut())
-                    .entry(mnemonic_string.clone())
-                    .or_insert_with(|| {
-                        panic!("the opcode `{}` has not been defined", mnemonic_string)
-                    });
-            });
-            let action_name = format!("exec_{}", mnemonic_string.to_lowercase());
+            let action_name = format!("exec_{}", mnemonic.to_string().to_lowercase());
             let action_ident =
                 syn::Ident::new(action_name.as_str(), proc_macro2::Span::call_site());
-            action_array[value as usize] = action_ident.clone();
-            let action_impl = quote!(#[inline] pub fn #action_ident () #block);
+            let action_impl = quote!(
+                #[inline]
+                #[allow(unused_variables)]
+                pub fn #action_ident (#inputs) #output #block
+            );
+            mnemonics.push(mnemonic);
+            action_idents.push(action_ident);
             action_impls.push(action_impl);
         }
         Constructor {
             name,
-            actions: action_array,
+            opkind,
+            inputs,
+            output,
+            mnemonics,
+            actions: action_idents,
             action_impls,
             unknown,
         }
@@ -56,22 +63,40 @@ impl Constructor {
 
     fn output(&self) -> proc_macro2::TokenStream {
         let group_name = &self.name;
+        let opkind = &self.opkind;
+        let mnemonics = &self.mnemonics;
         let actions = &self.actions;
         let action_impls = &self.action_impls;
         let unknown = &self.unknown;
+        let inputs = &self.inputs;
+        let output = &self.output;
         let module_name = syn::Ident::new(
             self.name.to_string().to_lowercase().as_str(),
             proc_macro2::Span::call_site(),
         );
-        let module_names = vec![&module_name; 256];
+        let module_names = vec![&module_name; actions.len()];
+        let opkinds = vec![opkind; actions.len()];
+        // only `pattern: Type` is accepted when parsing
+        let input_types = inputs.iter().filter_map(|input| match *input {
+            syn::FnArg::Captured(ref arg) => Some(&arg.ty),
+            _ => None,
+        });
+        let func = quote!(fn(#(#input_types),*) #output);
+        // The values are resolved by the compiler from the `OpKind`, so an undefined mnemonic is
+        // reported at its span.
         quote!(
-            pub const #group_name: [ActionFunc; 256] = [
-                #(#module_names::#actions,)*
-            ];
+            pub const #group_name: [#func; 256] = {
+                let mut actions = [#module_name::exec_unknown as #func; 256];
+                #(actions[#opkinds::#mnemonics as usize] = #module_names::#actions;)*
+                actions
+            };
             mod #module_name {
+                #[allow(unused_imports)]
+                use super::*;
                 #(#action_impls)*
                 #[inline]
-                pub fn exec_unknown() #unknown
+                #[allow(unused_variables)]
+                pub fn exec_unknown(#inputs) #output #unknown
             }
         )
     }
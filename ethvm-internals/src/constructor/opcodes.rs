@@ -55,7 +55,7 @@ impl Constructor {
         let outputs = proc_macro2::TokenStream::from_iter(self.output.take());
         let opcode_impls = proc_macro2::TokenStream::from_iter(self.opcode_impls.take());
         let opstmt_impls = proc_macro2::TokenStream::from_iter(self.opstmt_impls.take());
-        quote!(
+        let output = quote!(
             #outputs
             impl OpCode {
                 #opcode_impls
@@ -63,7 +63,15 @@ impl Constructor {
             impl OpCodeStmt {
                 #opstmt_impls
             }
-        )
+        );
+        match self.opset.module {
+            Some((ref visibility, ref name)) => quote!(
+                #visibility mod #name {
+                    #output
+                }
+            ),
+            None => output,
+        }
     }
 
     fn clear(&self) {
@@ -76,6 +84,7 @@ impl Constructor {
         self.clear();
         self.def_error();
         self.def_definition();
+        self.def_opkind();
         self.defun_utils();
         self.impl_std_fmt_display();
         self.impl_std_convert_into_bytes();
@@ -88,7 +97,10 @@ impl Constructor {
 
     fn def_error(&self) {
         let part = quote!(
-            mod error {
+            pub mod error {
+                /// The value is not an opcode.
+                #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+                pub struct UnknownValue(pub u8);
                 #[derive(Debug, Clone, Copy)]
                 pub enum FromValueSlice {
                     BadSizeSince(usize),
@@ -145,7 +157,7 @@ impl Constructor {
             ///
             /// Defined by the proc-macro [`define_opcodes`].
             ///
-            /// [`define_opcodes`]: ../ethvm_internals/fn.define_opcodes.html
+            /// [`define_opcodes`]: ::ethvm_internals::define_opcodes
             /// [Ethereum Yellow Paper]: https://ethereum.github.io/yellowpaper/paper.pdf
             #[derive(Debug, Clone, PartialEq, Eq)]
             pub enum OpCode {
@@ -157,14 +169,141 @@ impl Constructor {
             ///
             /// Defined by the proc-macro [`define_opcodes`].
             ///
-            /// [`OpCode`]: ./enum.OpCode.html
-            /// [`define_opcodes`]: ../ethvm_internals/fn.define_opcodes.html
+            /// [`define_opcodes`]: ::ethvm_internals::define_opcodes
             #[derive(Debug, Clone, PartialEq, Eq)]
             pub struct OpCodeStmt (Vec<OpCode>);
         );
         self.append(part);
     }
 
+    fn def_opkind(&self) {
+        let core = &self.opset.for_each_construct(
+            |value, mnemonic, _delta, _alpha| quote!(#mnemonic = #value),
+            |value, mnemonic, _delta, _alpha, _iv1_size| quote!(#mnemonic = #value),
+        );
+        let from_value = &self.opset.for_each_construct(
+            |value, mnemonic, _delta, _alpha| quote!(#value => Ok(OpKind::#mnemonic)),
+            |value, mnemonic, _delta, _alpha, _iv1_size| quote!(#value => Ok(OpKind::#mnemonic)),
+        );
+        let from_opcode = &self.opset.for_each_construct(
+            |_value, mnemonic, _delta, _alpha| quote!(OpCode::#mnemonic => Some(OpKind::#mnemonic)),
+            |_value, mnemonic, _delta, _alpha, _iv1_size| {
+                quote!(OpCode::#mnemonic(..) => Some(OpKind::#mnemonic))
+            },
+        );
+        let mnemonic = &self.opset.for_each_construct(
+            |_value, mnemonic, _delta, _alpha| quote!(OpKind::#mnemonic => stringify!(#mnemonic)),
+            |_value, mnemonic, _delta, _alpha, _iv1_size| {
+                quote!(OpKind::#mnemonic => stringify!(#mnemonic))
+            },
+        );
+        let immediate_size = &self.opset.for_each_construct(
+            |_value, mnemonic, _delta, _alpha| quote!(OpKind::#mnemonic => 0),
+            |_value, mnemonic, _delta, _alpha, iv1_size| quote!(OpKind::#mnemonic => #iv1_size),
+        );
+        let delta = &self.opset.for_each_construct(
+            |_value, mnemonic, delta, _alpha| quote!(OpKind::#mnemonic => #delta),
+            |_value, mnemonic, delta, _alpha, _iv1_size| quote!(OpKind::#mnemonic => #delta),
+        );
+        let alpha = &self.opset.for_each_construct(
+            |_value, mnemonic, _delta, alpha| quote!(OpKind::#mnemonic => #alpha),
+            |_value, mnemonic, _delta, alpha, _iv1_size| quote!(OpKind::#mnemonic => #alpha),
+        );
+        let part = quote!(
+            /// The kind of an [`OpCode`] without its immediate values, which is a single byte.
+            ///
+            /// Defined by the proc-macro [`define_opcodes`].
+            ///
+            /// [`define_opcodes`]: ::ethvm_internals::define_opcodes
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            #[repr(u8)]
+            pub enum OpKind {
+                #(#core,)*
+            }
+
+            impl OpKind {
+                /// Get the value of an opcode.
+                #[inline]
+                pub fn value(self) -> u8 {
+                    self as u8
+                }
+                #[inline]
+                pub fn mnemonic(self) -> &'static str {
+                    match self {
+                        #(#mnemonic,)*
+                    }
+                }
+                /// The size of the immediate value, in bytes.
+                #[inline]
+                pub fn immediate_size(self) -> usize {
+                    match self {
+                        #(#immediate_size,)*
+                    }
+                }
+                /// For each opcode, the items removed from stack.
+                #[inline]
+                pub fn stack_removed(self) -> u8 {
+                    match self {
+                        #(#delta,)*
+                    }
+                }
+                /// For each opcode, the additional items placed on the stack.
+                #[inline]
+                pub fn stack_placed(self) -> u8 {
+                    match self {
+                        #(#alpha,)*
+                    }
+                }
+            }
+
+            impl ::std::fmt::Display for OpKind {
+                #[inline]
+                fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                    write!(f, "{}", self.mnemonic())
+                }
+            }
+
+            impl ::std::convert::TryFrom<u8> for OpKind {
+                type Error = self::error::UnknownValue;
+                #[inline]
+                fn try_from(value: u8) -> Result<Self, Self::Error> {
+                    match value {
+                        #(#from_value,)*
+                        v => Err(self::error::UnknownValue(v)),
+                    }
+                }
+            }
+
+            impl ::std::convert::From<OpKind> for u8 {
+                #[inline]
+                fn from(kind: OpKind) -> Self {
+                    kind as u8
+                }
+            }
+
+            impl<'a> ::std::convert::TryFrom<&'a OpCode> for OpKind {
+                type Error = self::error::UnknownValue;
+                /// Drop the immediate values, fail for `OpCode::UNKNOWN`.
+                #[inline]
+                fn try_from(opcode: &OpCode) -> Result<Self, Self::Error> {
+                    opcode.kind().ok_or_else(|| self::error::UnknownValue(opcode.value()))
+                }
+            }
+        );
+        self.append(part);
+        let part = quote!(
+            /// Get the kind of an opcode, `None` for `OpCode::UNKNOWN`.
+            #[inline]
+            pub fn kind(&self) -> Option<OpKind> {
+                match *self {
+                    #(#from_opcode,)*
+                    OpCode::UNKNOWN(_) => None,
+                }
+            }
+        );
+        self.impl_opcode(part);
+    }
+
     fn defun_utils(&self) {
         let part = quote!(
             #[inline]
@@ -6,6 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::collections::HashSet;
+
 use syn;
 
 #[derive(Clone)]
@@ -27,6 +29,10 @@ impl syn::parse::Parse for Action {
 #[derive(Clone)]
 pub struct ActionGroup {
     pub name: syn::Ident,
+    // the path of the `OpKind` to resolve the mnemonics, `OpKind` if not given
+    pub opkind: syn::Path,
+    pub inputs: syn::punctuated::Punctuated<syn::FnArg, Token![,]>,
+    pub output: syn::ReturnType,
     pub actions: Vec<Action>,
     pub unknown: syn::Block,
 }
@@ -34,18 +40,54 @@ pub struct ActionGroup {
 impl syn::parse::Parse for ActionGroup {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let content;
+        let content_inputs;
         let name = input.parse()?;
         let _: Token![,] = input.parse()?;
+        let opkind = if input.peek(Token![fn]) {
+            parse_quote!(OpKind)
+        } else {
+            let opkind = input.parse()?;
+            let _: Token![,] = input.parse()?;
+            opkind
+        };
+        let _: Token![fn] = input.parse()?;
+        let _ = parenthesized!(content_inputs in input);
+        let inputs: syn::punctuated::Punctuated<syn::FnArg, Token![,]> =
+            content_inputs.parse_terminated(syn::parse::Parse::parse)?;
+        for input in inputs.iter() {
+            if let syn::FnArg::Captured(_) = *input {
+                continue;
+            }
+            return Err(syn::Error::new_spanned(
+                input,
+                "the arguments of actions should be `pattern: Type`",
+            ));
+        }
+        let output = input.parse()?;
+        let _: Token![,] = input.parse()?;
         let _ = bracketed!(content in input);
         let actions = {
             let actions: syn::punctuated::Punctuated<Action, Token![,]> =
                 content.parse_terminated(syn::parse::Parse::parse)?;
+            let mut mnemonics = HashSet::new();
+            for action in actions.iter() {
+                let mnemonic = action.mnemonic.to_string();
+                if !mnemonics.insert(mnemonic.clone()) {
+                    return Err(syn::Error::new(
+                        action.mnemonic.span(),
+                        format!("the action of `{}` has been defined twice", mnemonic),
+                    ));
+                }
+            }
             actions.into_iter().collect()
         };
         let _: Token![,] = input.parse()?;
         let unknown = input.parse()?;
         Ok(ActionGroup {
             name,
+            opkind,
+            inputs,
+            output,
             actions,
             unknown,
         })
@@ -10,4 +10,4 @@ mod actions;
 mod opcodes;
 
 pub use self::actions::{Action, ActionGroup};
-pub use self::opcodes::{OpCode, OpCodeSet};
+pub use self::opcodes::OpCodeSet;
@@ -6,13 +6,15 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::collections::HashMap;
+
 use proc_macro2;
 use syn;
 
-use caches;
-
 #[derive(Clone)]
 pub struct OpCode {
+    // the span of the whole tuple, for diagnostics
+    pub span: proc_macro2::Span,
     pub value: syn::LitInt,
     pub mnemonic: syn::Ident,
     pub immediate_vec: Vec<syn::LitInt>,
@@ -25,8 +27,17 @@ pub struct OpCode {
 impl syn::parse::Parse for OpCode {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let content;
-        let _ = parenthesized!(content in input);
-        let value = content.parse()?;
+        let span = parenthesized!(content in input).span;
+        let value: syn::LitInt = content.parse()?;
+        if value.value() > 255 {
+            return Err(syn::Error::new(
+                value.span(),
+                format!(
+                    "the value ({}) of an opcode should be in [0, 255]",
+                    value.value()
+                ),
+            ));
+        }
         let _: Token![,] = content.parse()?;
         let mnemonic = content.parse()?;
         let _: Token![,] = content.parse()?;
@@ -35,22 +46,38 @@ impl syn::parse::Parse for OpCode {
             let _ = bracketed!(content_immediate in content);
             let immediate_vec: syn::punctuated::Punctuated<syn::LitInt, Token![,]> =
                 content_immediate.parse_terminated(syn::parse::Parse::parse)?;
+            if let Some(extra) = immediate_vec.iter().nth(1) {
+                return Err(syn::Error::new(
+                    extra.span(),
+                    "at most one immediate value is supported",
+                ));
+            }
             immediate_vec
                 .into_iter()
                 .map(|i| {
-                    syn::LitInt::new(
+                    if i.value() < 1 || i.value() > 32 {
+                        return Err(syn::Error::new(
+                            i.span(),
+                            format!(
+                                "the size ({}) of an immediate value should be in [1, 32]",
+                                i.value()
+                            ),
+                        ));
+                    }
+                    Ok(syn::LitInt::new(
                         i.value(),
                         syn::IntSuffix::None,
                         proc_macro2::Span::call_site(),
-                    )
+                    ))
                 })
-                .collect()
+                .collect::<syn::Result<_>>()?
         };
         let _: Token![,] = content.parse()?;
         let delta = content.parse()?;
         let _: Token![,] = content.parse()?;
         let alpha = content.parse()?;
         Ok(OpCode {
+            span,
             value,
             mnemonic,
             immediate_vec,
@@ -61,59 +88,59 @@ impl syn::parse::Parse for OpCode {
 }
 
 impl OpCode {
+    /// The value is checked to be in `[0, 255]` when parsing.
     pub fn value(&self) -> u8 {
-        let value = self.value.value();
-        if value > 255 {
-            panic!(
-                "The value ({}) of the OpCode({}) should in [0, 255].",
-                value, self.mnemonic,
-            );
-        }
-        value as u8
+        self.value.value() as u8
     }
 }
 
 #[derive(Clone)]
 pub struct OpCodeSet {
+    // the module which contains the definitions, e.g. `pub mod eof`, if given
+    pub module: Option<(syn::Visibility, syn::Ident)>,
     pub opcodes: Vec<OpCode>,
 }
 
 impl syn::parse::Parse for OpCodeSet {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let module = if input.peek(syn::token::Bracket) {
+            None
+        } else {
+            let visibility = input.parse()?;
+            let _: Token![mod] = input.parse()?;
+            let name = input.parse()?;
+            let _: Token![,] = input.parse()?;
+            Some((visibility, name))
+        };
         let content;
         let _ = bracketed!(content in input);
         let opcodes = {
             let opcodes: syn::punctuated::Punctuated<OpCode, Token![,]> =
                 content.parse_terminated(syn::parse::Parse::parse)?;
-            opcodes
-                .into_iter()
-                .map(|opcode| {
-                    let value = opcode.value();
-                    let mnemonic = &opcode.mnemonic.to_string();
-                    caches::OPCODE_TABLE.with(|f| {
-                        (*f.borrow_mut())
-                            .entry(mnemonic.clone())
-                            .and_modify(|_| {
-                                panic!("the opcode `{}` has been defined twice", mnemonic)
-                            })
-                            .or_insert_with(|| value);
-                    });
-                    caches::OPCODE_VALUE_TABLE.with(|f| {
-                        (*f.borrow_mut())
-                            .entry(value)
-                            .and_modify(|mnemonic_old| {
-                                panic!(
-                                    "the value `{:#04x}` has been used twice ({} and {})",
-                                    value, mnemonic_old, mnemonic
-                                )
-                            })
-                            .or_insert_with(|| mnemonic.clone());
-                    });
-                    opcode
-                })
-                .collect()
+            let mut mnemonic_to_value = HashMap::new();
+            let mut value_to_mnemonic = HashMap::new();
+            for opcode in opcodes.iter() {
+                let value = opcode.value();
+                let mnemonic = &opcode.mnemonic.to_string();
+                if mnemonic_to_value.insert(mnemonic.clone(), value).is_some() {
+                    return Err(syn::Error::new(
+                        opcode.span,
+                        format!("the opcode `{}` has been defined twice", mnemonic),
+                    ));
+                }
+                if let Some(mnemonic_old) = value_to_mnemonic.insert(value, mnemonic.clone()) {
+                    return Err(syn::Error::new(
+                        opcode.span,
+                        format!(
+                            "the value `{:#04x}` has been used twice ({} and {})",
+                            value, mnemonic_old, mnemonic
+                        ),
+                    ));
+                }
+            }
+            opcodes.into_iter().collect()
         };
-        Ok(OpCodeSet { opcodes })
+        Ok(OpCodeSet { module, opcodes })
     }
 }
 
@@ -138,11 +165,12 @@ impl OpCodeSet {
                     ref immediate_vec,
                     ref delta,
                     ref alpha,
+                    ..
                 } = opcode;
                 if immediate_vec.is_empty() {
                     f(value, mnemonic, delta, alpha)
                 } else {
-                    // now, the size of immediate values only can be 0 or 1
+                    // there is at most one immediate value, checked when parsing
                     let iv1_size = immediate_vec.first().unwrap();
                     g(value, mnemonic, delta, alpha, iv1_size)
                 }
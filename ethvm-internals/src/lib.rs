@@ -18,8 +18,6 @@ extern crate syn;
 #[macro_use]
 extern crate quote;
 
-mod caches;
-
 mod definition;
 
 mod constructor;
@@ -47,7 +45,9 @@ mod constructor;
 /// );
 /// ```
 ///
-/// The input for this macro is a list.
+/// The input for this macro is a list, optionally after a module, e.g. `pub mod eof,`, which
+/// contains the definitions. Several instruction sets can be defined side by side in different
+/// modules.
 ///
 /// Each element in the list is a tuple:
 /// - The 1st element in the tuple is the value of the opcode.
@@ -55,6 +55,45 @@ mod constructor;
 /// - The 3rd element is an array of immediate values's sizes.
 /// - The 4th element is the size of the items removed from stack.
 /// - The 5th element is the size of the additional items placed on the stack.
+///
+/// Each value is in `[0, 255]`, each mnemonic and each value are used once, and an opcode has at
+/// most one immediate value whose size is in `[1, 32]`, otherwise a compile error points at the
+/// offending tuple:
+///
+/// ```compile_fail
+/// # extern crate ethvm_internals;
+/// // the immediate value is too large
+/// ethvm_internals::define_opcodes!([(0x60, PUSH1, [33], 0, 1)]);
+/// # fn main() {}
+/// ```
+///
+/// ```compile_fail
+/// # extern crate ethvm_internals;
+/// // the mnemonic is used twice
+/// ethvm_internals::define_opcodes!([(0x00, STOP, [], 0, 0), (0x01, STOP, [], 0, 0)]);
+/// # fn main() {}
+/// ```
+///
+/// ```compile_fail
+/// # extern crate ethvm_internals;
+/// // the value is used twice
+/// ethvm_internals::define_opcodes!([(0x00, STOP, [], 0, 0), (0x00, ADD, [], 2, 1)]);
+/// # fn main() {}
+/// ```
+///
+/// ```compile_fail
+/// # extern crate ethvm_internals;
+/// // the value is not a byte
+/// ethvm_internals::define_opcodes!([(0x100, STOP, [], 0, 0)]);
+/// # fn main() {}
+/// ```
+///
+/// ```compile_fail
+/// # extern crate ethvm_internals;
+/// // more than one immediate value
+/// ethvm_internals::define_opcodes!([(0x60, PUSH1, [1, 1], 0, 1)]);
+/// # fn main() {}
+/// ```
 #[proc_macro]
 pub fn define_opcodes(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let inputs = parse_macro_input!(input as definition::OpCodeSet);
@@ -72,6 +111,7 @@ pub fn define_opcodes(input: proc_macro::TokenStream) -> proc_macro::TokenStream
 /// ```ignore
 /// create_action_groups!(
 ///     GROUP_NAME,
+///     fn(vm: &mut Interpreter, host: &mut Host) -> Result<(), Halt>,
 ///     [
 ///         |STOP| {
 ///             ...
@@ -87,15 +127,53 @@ pub fn define_opcodes(input: proc_macro::TokenStream) -> proc_macro::TokenStream
 /// );
 /// ```
 ///
-/// The input for this macro is an ident, a list and a block.
+/// The input for this macro is an ident, an optional path, a function signature, a list and a
+/// block.
 ///
 /// The ident is the name of this action group.
 ///
+/// The path is the `OpKind` of the instruction set, e.g. `eof::OpKind`, the `OpKind` in scope is
+/// used if it is not given.
+///
+/// The function signature is shared by all actions, the arguments can be used in the actions.
+/// The action group is an array of 256 function pointers of this signature, indexed by the
+/// values of opcodes.
+///
 /// Each element in the list is a closure expression.
 /// But there is only one ident between `|`s, and it is the [`OpCode`].
 ///
 /// The last block is the action for an unknown [`OpCode`].
 ///
+/// The mnemonics are resolved against the `OpKind` defined by `define_opcodes!`, so the two
+/// macros can be used in any order and in different crates. An undefined mnemonic is reported
+/// by the compiler at its span, and so is a mnemonic which has more than one action:
+///
+/// ```compile_fail
+/// # extern crate ethvm_internals;
+/// ethvm_internals::define_opcodes!([(0x00, STOP, [], 0, 0), (0x60, PUSH1, [1], 0, 1)]);
+/// // `ADD` is not defined
+/// ethvm_internals::create_action_groups!(
+///     ACTIONS,
+///     fn(x: u64) -> u64,
+///     [|STOP| { x }, |ADD| { x + 1 }],
+///     { 0 }
+/// );
+/// # fn main() {}
+/// ```
+///
+/// ```compile_fail
+/// # extern crate ethvm_internals;
+/// ethvm_internals::define_opcodes!([(0x00, STOP, [], 0, 0), (0x60, PUSH1, [1], 0, 1)]);
+/// // `PUSH1` has two actions
+/// ethvm_internals::create_action_groups!(
+///     ACTIONS,
+///     fn(x: u64) -> u64,
+///     [|PUSH1| { x }, |PUSH1| { x + 1 }],
+///     { 0 }
+/// );
+/// # fn main() {}
+/// ```
+///
 /// [`OpCode`]: ../ethvm/enum.OpCode.html
 #[proc_macro]
 pub fn create_action_groups(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
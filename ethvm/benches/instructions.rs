@@ -0,0 +1,55 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Compare scanning bytecode with `Instructions` and decoding it with `OpCodeStmt`.
+
+#[macro_use]
+extern crate bencher;
+extern crate ethvm;
+
+use bencher::{black_box, Bencher};
+
+use ethvm::schedule::MAX_CODE_SIZE;
+use ethvm::{Instructions, OpCodeStmt};
+
+/// Pseudo-random bytecode of the maximum size, about an eighth of the bytes are `PUSH*`.
+fn code() -> Vec<u8> {
+    let mut seed = 0x2545_f491_4f6c_dd1du64;
+    (0..MAX_CODE_SIZE)
+        .map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed as u8
+        })
+        .collect()
+}
+
+fn instructions(b: &mut Bencher) {
+    let code = code();
+    b.bytes = code.len() as u64;
+    b.iter(|| {
+        Instructions::new(black_box(&code[..])).fold(0, |x, (pc, opcode, immediate)| {
+            x ^ pc ^ usize::from(opcode) ^ immediate.len()
+        })
+    });
+}
+
+fn decode(b: &mut Bencher) {
+    let code = code();
+    b.bytes = code.len() as u64;
+    b.iter(|| {
+        let stmt = OpCodeStmt::from_value_slice_allow_unknown(black_box(&code[..])).unwrap();
+        stmt.as_slice()
+            .iter()
+            .fold(0, |x, opcode| x ^ usize::from(opcode.value()))
+    });
+}
+
+benchmark_group!(benches, instructions, decode);
+benchmark_main!(benches);
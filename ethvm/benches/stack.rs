@@ -0,0 +1,114 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Compare `Stack` with the previous stack, which kept all 1024 items inline.
+
+#[macro_use]
+extern crate bencher;
+extern crate ethvm;
+
+use bencher::{black_box, Bencher};
+
+use ethvm::stack::Stack;
+
+/// The previous stack, as the baseline, which is benchmarked inline as it was used.
+#[derive(Clone)]
+struct ArrayStack {
+    data: [[u8; 32]; 1024],
+    ptr: usize,
+    zeros: [u8; 32],
+}
+
+impl Default for ArrayStack {
+    fn default() -> Self {
+        ArrayStack {
+            data: [[0; 32]; 1024],
+            ptr: 0,
+            zeros: [0; 32],
+        }
+    }
+}
+
+impl ArrayStack {
+    fn push(&mut self, input: &[u8]) -> Result<(), ()> {
+        if input.len() > 32 || self.ptr >= 1024 {
+            return Err(());
+        }
+        self.data[self.ptr][32 - input.len()..].copy_from_slice(input);
+        self.ptr += 1;
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Result<[u8; 32], ()> {
+        if self.ptr == 0 {
+            return Err(());
+        }
+        self.ptr -= 1;
+        let ret = self.data[self.ptr];
+        self.data[self.ptr].copy_from_slice(&self.zeros[..]);
+        Ok(ret)
+    }
+}
+
+/// The depth of the stack in the benchmarks of cloning, typical for real contracts.
+const DEPTH: usize = 16;
+
+fn create(b: &mut Bencher) {
+    b.iter(|| black_box(Stack::default()));
+}
+
+fn create_baseline(b: &mut Bencher) {
+    b.iter(|| black_box(ArrayStack::default()));
+}
+
+fn push_pop(b: &mut Bencher) {
+    let mut stack = Stack::default();
+    b.iter(|| {
+        for i in 0..1024usize {
+            stack.push(&i.to_be_bytes()[..]).unwrap();
+        }
+        (0..1024).fold(0, |x, _| x ^ stack.pop().unwrap()[31])
+    });
+}
+
+fn push_pop_baseline(b: &mut Bencher) {
+    let mut stack = ArrayStack::default();
+    b.iter(|| {
+        for i in 0..1024usize {
+            stack.push(&i.to_be_bytes()[..]).unwrap();
+        }
+        (0..1024).fold(0, |x, _| x ^ stack.pop().unwrap()[31])
+    });
+}
+
+fn clone(b: &mut Bencher) {
+    let mut stack = Stack::default();
+    for i in 0..DEPTH {
+        stack.push(&i.to_be_bytes()[..]).unwrap();
+    }
+    b.iter(|| black_box(stack.clone()));
+}
+
+fn clone_baseline(b: &mut Bencher) {
+    let mut stack = ArrayStack::default();
+    for i in 0..DEPTH {
+        stack.push(&i.to_be_bytes()[..]).unwrap();
+    }
+    b.iter(|| black_box(stack.clone()));
+}
+
+benchmark_group!(
+    benches,
+    create,
+    create_baseline,
+    push_pop,
+    push_pop_baseline,
+    clone,
+    clone_baseline
+);
+benchmark_main!(benches);
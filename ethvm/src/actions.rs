@@ -6,22 +6,873 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::cmp::Ordering;
+
 use ethvm_internals;
 
-// TODO Unimplemented!
-pub type ActionFunc = fn();
+use host::{CallKind, Host, Message};
+use inspector::Inspector;
+use interpreter::{CallRequest, Exit, Halt, Interpreter};
+#[cfg(feature = "keccak")]
+use keccak;
+use log::Log;
+use opcodes::{eof, OpKind};
+use schedule::{
+    copy_gas, GAS_BASE, GAS_BLOCKHASH, GAS_CALL_STIPEND, GAS_CALL_VALUE, GAS_COLD_ACCOUNT_ACCESS,
+    GAS_COLD_SLOAD, GAS_EXP, GAS_HIGH, GAS_JUMPDEST, GAS_LOG, GAS_LOG_DATA, GAS_LOG_TOPIC, GAS_LOW,
+    GAS_MID, GAS_NEW_ACCOUNT, GAS_VERYLOW, GAS_WARM_STORAGE_READ,
+};
+#[cfg(feature = "keccak")]
+use schedule::{GAS_CREATE, GAS_INITCODE_WORD, GAS_SHA3, GAS_SHA3_WORD};
+use types::{Address, H256};
+use u256::U256;
+
+#[inline]
+fn bool_to_u256(v: bool) -> U256 {
+    if v {
+        U256::ONE
+    } else {
+        U256::ZERO
+    }
+}
+
+#[inline]
+fn unary<F>(vm: &mut Interpreter, gas: u64, f: F) -> Result<(), Exit>
+where
+    F: FnOnce(U256) -> U256,
+{
+    vm.charge(gas)?;
+    let a = vm.pop()?;
+    vm.push(f(a))
+}
+
+#[inline]
+fn binary<F>(vm: &mut Interpreter, gas: u64, f: F) -> Result<(), Exit>
+where
+    F: FnOnce(U256, U256) -> U256,
+{
+    vm.charge(gas)?;
+    let a = vm.pop()?;
+    let b = vm.pop()?;
+    vm.push(f(a, b))
+}
+
+#[inline]
+fn ternary<F>(vm: &mut Interpreter, gas: u64, f: F) -> Result<(), Exit>
+where
+    F: FnOnce(U256, U256, U256) -> U256,
+{
+    vm.charge(gas)?;
+    let a = vm.pop()?;
+    let b = vm.pop()?;
+    let c = vm.pop()?;
+    vm.push(f(a, b, c))
+}
+
+/// Copy `source[offset..offset + 32]` as a word, pad zeros if out of range.
+#[inline]
+fn load_word(source: &[u8], offset: U256) -> U256 {
+    let mut word = [0u8; 32];
+    if let Some(offset) = offset.to_usize() {
+        if offset < source.len() {
+            let end = ::std::cmp::min(source.len(), offset + 32);
+            word[..end - offset].copy_from_slice(&source[offset..end]);
+        }
+    }
+    U256::from_be_bytes(word)
+}
+
+/// Charge for copy `size` bytes into the memory at `offset`, and expand the memory.
+#[inline]
+fn prepare_copy(vm: &mut Interpreter, offset: U256, size: U256) -> Result<(usize, usize), Exit> {
+    let (offset, size) = vm.expand_memory(offset, size)?;
+    vm.charge(copy_gas(size as u64))?;
+    Ok((offset, size))
+}
+
+#[inline]
+fn push_n(vm: &mut Interpreter, n: usize) -> Result<(), Exit> {
+    vm.charge(GAS_VERYLOW)?;
+    let mut word = [0u8; 32];
+    let start = ::std::cmp::min(vm.pc, vm.code.len());
+    let end = ::std::cmp::min(vm.pc + n, vm.code.len());
+    word[32 - n..32 - n + end - start].copy_from_slice(&vm.code[start..end]);
+    vm.pc += n;
+    Ok(vm.stack.push(&word[..])?)
+}
+
+#[inline]
+fn dup_n(vm: &mut Interpreter, n: usize) -> Result<(), Exit> {
+    vm.charge(GAS_VERYLOW)?;
+    Ok(vm.stack.dup(n)?)
+}
+
+#[inline]
+fn swap_n(vm: &mut Interpreter, n: usize) -> Result<(), Exit> {
+    vm.charge(GAS_VERYLOW)?;
+    Ok(vm.stack.swap(n)?)
+}
+
+/// Read the immediate byte of `DUPN`, `SWAPN` and `EXCHANGE`, it is zero after the end of code.
+#[inline]
+fn immediate_byte(vm: &mut Interpreter) -> usize {
+    let byte = vm.code.get(vm.pc).cloned().unwrap_or(0);
+    vm.pc += 1;
+    usize::from(byte)
+}
+
+#[inline]
+fn log_n(vm: &mut Interpreter, host: &mut Host, n: usize) -> Result<(), Exit> {
+    vm.check_static()?;
+    vm.charge(GAS_LOG + GAS_LOG_TOPIC * n as u64)?;
+    let offset = vm.pop()?;
+    let size = vm.pop()?;
+    let mut topics = Vec::with_capacity(n);
+    for _ in 0..n {
+        topics.push(H256(vm.stack.pop()?));
+    }
+    let (offset, size) = vm.expand_memory(offset, size)?;
+    vm.charge(GAS_LOG_DATA.saturating_mul(size as u64))?;
+    let data = vm.memory.get(offset, size).to_vec();
+    host.logs.push(Log {
+        address: vm.address,
+        topics,
+        data,
+    });
+    Ok(())
+}
+
+#[cfg(feature = "keccak")]
+#[inline]
+fn sha3(vm: &mut Interpreter) -> Result<(), Exit> {
+    let offset = vm.pop()?;
+    let size = vm.pop()?;
+    let (offset, size) = vm.expand_memory(offset, size)?;
+    vm.charge(GAS_SHA3 + GAS_SHA3_WORD * (size as u64).div_ceil(32))?;
+    let hash = keccak::keccak256(vm.memory.get(offset, size));
+    vm.push(hash.into())
+}
+
+#[cfg(not(feature = "keccak"))]
+#[inline]
+fn sha3(_vm: &mut Interpreter) -> Result<(), Exit> {
+    Err(Halt::InvalidOpcode(0x20).into())
+}
+
+#[cfg(feature = "keccak")]
+#[inline]
+fn extcodehash(vm: &mut Interpreter, host: &mut Host) -> Result<(), Exit> {
+    let address = Address::from(vm.pop()?);
+    vm.charge(host.schedule.extcodehash_gas + cold_account_gas(host, &address))?;
+    if host.state.is_dead(&address) {
+        vm.push(U256::ZERO)
+    } else {
+        let hash = host.code_hash(&address);
+        vm.push(hash.into())
+    }
+}
+
+#[cfg(not(feature = "keccak"))]
+#[inline]
+fn extcodehash(_vm: &mut Interpreter, _host: &mut Host) -> Result<(), Exit> {
+    Err(Halt::InvalidOpcode(0x3f).into())
+}
+
+/// Store a word, with the net gas metering since Istanbul (EIP-1283, EIP-2200).
+fn sstore(vm: &mut Interpreter, host: &mut Host) -> Result<(), Exit> {
+    vm.check_static()?;
+    let key = vm.pop()?;
+    let value = vm.pop()?;
+    if host.schedule.sstore_sentry && vm.gas <= GAS_CALL_STIPEND {
+        return Err(Halt::OutOfGas.into());
+    }
+    let address = vm.address;
+    let current = host.state.storage(&address, &key);
+    let mut gas = 0;
+    if host.schedule.have_access_list && host.access_storage(&address, key) {
+        gas += GAS_COLD_SLOAD;
+    }
+    let schedule = &host.schedule;
+    let (set_gas, reset_gas, clears_refund) = (
+        schedule.sstore_set_gas,
+        schedule.sstore_reset_gas,
+        schedule.sstore_clears_refund,
+    );
+    if !schedule.net_sstore_metering {
+        gas += if current.is_zero() && !value.is_zero() {
+            set_gas
+        } else {
+            reset_gas
+        };
+        vm.charge(gas)?;
+        if !current.is_zero() && value.is_zero() {
+            host.refund += clears_refund;
+        }
+        host.set_storage(&address, key, value);
+        return Ok(());
+    }
+    let dirty_gas = schedule.sload_gas;
+    let original = host.original_storage(&address, &key);
+    if current == value {
+        gas += dirty_gas;
+    } else if original == current {
+        gas += if original.is_zero() {
+            set_gas
+        } else {
+            reset_gas
+        };
+    } else {
+        gas += dirty_gas;
+    }
+    vm.charge(gas)?;
+    if current != value {
+        if original == current {
+            if !original.is_zero() && value.is_zero() {
+                host.refund += clears_refund;
+            }
+        } else {
+            if !original.is_zero() {
+                if current.is_zero() {
+                    host.refund -= clears_refund;
+                } else if value.is_zero() {
+                    host.refund += clears_refund;
+                }
+            }
+            if original == value {
+                // The slot is restored, the cost is refunded except the cost of a dirty slot.
+                host.refund += if original.is_zero() {
+                    set_gas - dirty_gas
+                } else {
+                    reset_gas - dirty_gas
+                };
+            }
+        }
+    }
+    host.set_storage(&address, key, value);
+    Ok(())
+}
+
+/// The additional gas cost to access an account for the first time (EIP-2929).
+#[inline]
+fn cold_account_gas(host: &mut Host, address: &Address) -> u64 {
+    if host.schedule.have_access_list && host.access_address(address) {
+        GAS_COLD_ACCOUNT_ACCESS - GAS_WARM_STORAGE_READ
+    } else {
+        0
+    }
+}
+
+/// Prepare a sub-call, the frame is suspended until the sub-call finishes.
+fn call(vm: &mut Interpreter, host: &mut Host, kind: CallKind) -> Result<(), Exit> {
+    let gas = vm.pop()?;
+    let to = Address::from(vm.pop()?);
+    let value = if matches!(kind, CallKind::Call | CallKind::CallCode) {
+        vm.pop()?
+    } else {
+        U256::ZERO
+    };
+    let in_offset = vm.pop()?;
+    let in_size = vm.pop()?;
+    let out_offset = vm.pop()?;
+    let out_size = vm.pop()?;
+    let transfers_value = !value.is_zero();
+    if kind == CallKind::Call && transfers_value {
+        vm.check_static()?;
+    }
+    let (in_offset, in_size) = vm.expand_memory(in_offset, in_size)?;
+    let output_range = vm.expand_memory(out_offset, out_size)?;
+    let mut cost = host.schedule.call_gas + cold_account_gas(host, &to);
+    if transfers_value {
+        cost += GAS_CALL_VALUE;
+    }
+    if kind == CallKind::Call {
+        let creates_account = if host.schedule.no_empty {
+            transfers_value && host.state.is_dead(&to)
+        } else {
+            !host.state.exists(&to)
+        };
+        if creates_account {
+            cost += GAS_NEW_ACCOUNT;
+        }
+    }
+    vm.charge(cost)?;
+    let gas = if host.schedule.all_but_one_64th {
+        let cap = vm.gas - vm.gas / 64;
+        gas.to_u64().map_or(cap, |gas| ::std::cmp::min(gas, cap))
+    } else {
+        gas.to_u64().ok_or(Halt::OutOfGas)?
+    };
+    vm.charge(gas)?;
+    let gas = if transfers_value {
+        gas + GAS_CALL_STIPEND
+    } else {
+        gas
+    };
+    vm.return_data.clear();
+    if vm.depth >= host.config.call_depth_limit
+        || (transfers_value && host.state.balance(&vm.address) < value)
+    {
+        vm.return_gas(gas);
+        return vm.push(U256::ZERO);
+    }
+    let (caller, address, value) = match kind {
+        CallKind::CallCode => (vm.address, vm.address, value),
+        CallKind::DelegateCall => (vm.caller, vm.address, vm.value),
+        _ => (vm.address, to, value),
+    };
+    let message = Message {
+        kind,
+        caller,
+        address,
+        code_address: to,
+        value,
+        input: vm.memory.get(in_offset, in_size).to_vec(),
+        gas,
+        depth: vm.depth + 1,
+        is_static: vm.is_static || kind == CallKind::StaticCall,
+    };
+    Err(Exit::Call(Box::new(CallRequest {
+        message,
+        output_range,
+    })))
+}
+
+/// Prepare a contract creation, the frame is suspended until the init code finishes.
+#[cfg(feature = "keccak")]
+fn create(vm: &mut Interpreter, host: &mut Host, create2: bool) -> Result<(), Exit> {
+    vm.check_static()?;
+    let value = vm.pop()?;
+    let offset = vm.pop()?;
+    let size = vm.pop()?;
+    let kind = if create2 {
+        CallKind::Create2(vm.pop()?.into())
+    } else {
+        CallKind::Create
+    };
+    let (offset, size) = vm.expand_memory(offset, size)?;
+    let words = (size as u64).div_ceil(32);
+    let mut cost = GAS_CREATE;
+    if create2 {
+        cost += GAS_SHA3_WORD * words;
+    }
+    if host.schedule.limit_initcode_size {
+        if size > host.config.max_initcode_size() {
+            return Err(Halt::InitCodeSizeExceeded.into());
+        }
+        cost += GAS_INITCODE_WORD * words;
+    }
+    vm.charge(cost)?;
+    let gas = if host.schedule.all_but_one_64th {
+        vm.gas - vm.gas / 64
+    } else {
+        vm.gas
+    };
+    vm.charge(gas)?;
+    vm.return_data.clear();
+    if vm.depth >= host.config.call_depth_limit || host.state.balance(&vm.address) < value {
+        vm.return_gas(gas);
+        return vm.push(U256::ZERO);
+    }
+    let message = Message {
+        kind,
+        caller: vm.address,
+        // The address is derived by the host.
+        address: Address::default(),
+        code_address: Address::default(),
+        value,
+        input: vm.memory.get(offset, size).to_vec(),
+        gas,
+        depth: vm.depth + 1,
+        is_static: false,
+    };
+    Err(Exit::Call(Box::new(CallRequest {
+        message,
+        output_range: (0, 0),
+    })))
+}
+
+#[cfg(not(feature = "keccak"))]
+#[inline]
+fn create(_vm: &mut Interpreter, _host: &mut Host, create2: bool) -> Result<(), Exit> {
+    Err(Halt::InvalidOpcode(if create2 { 0xf5 } else { 0xf0 }).into())
+}
+
+#[inline]
+fn output(vm: &mut Interpreter) -> Result<(), Exit> {
+    let offset = vm.pop()?;
+    let size = vm.pop()?;
+    let (offset, size) = vm.expand_memory(offset, size)?;
+    vm.output = vm.memory.get(offset, size).to_vec();
+    Ok(())
+}
 
 ethvm_internals::create_action_groups!(
     ACTIONS_GROUP_DEFAULT,
+    fn(vm: &mut Interpreter, host: &mut Host) -> Result<(), Exit>,
+    [
+        |STOP| { Err(Halt::Stop.into()) },
+        |ADD| { binary(vm, GAS_VERYLOW, |a, b| a.wrapping_add(b)) },
+        |MUL| { binary(vm, GAS_LOW, |a, b| a.wrapping_mul(b)) },
+        |SUB| { binary(vm, GAS_VERYLOW, |a, b| a.wrapping_sub(b)) },
+        |DIV| { binary(vm, GAS_LOW, |a, b| a.evm_div(b)) },
+        |SDIV| { binary(vm, GAS_LOW, |a, b| a.signed_div(b)) },
+        |MOD| { binary(vm, GAS_LOW, |a, b| a.evm_rem(b)) },
+        |SMOD| { binary(vm, GAS_LOW, |a, b| a.signed_rem(b)) },
+        |ADDMOD| { ternary(vm, GAS_MID, |a, b, n| a.add_mod(b, n)) },
+        |MULMOD| { ternary(vm, GAS_MID, |a, b, n| a.mul_mod(b, n)) },
+        |EXP| {
+            let base = vm.pop()?;
+            let exponent = vm.pop()?;
+            let bytes = (exponent.bits() as u64).div_ceil(8);
+            vm.charge(GAS_EXP + host.schedule.exp_byte_gas * bytes)?;
+            vm.push(base.wrapping_pow(exponent))
+        },
+        |SIGNEXTEND| { binary(vm, GAS_LOW, |b, x| x.sign_extend(b)) },
+        |LT| { binary(vm, GAS_VERYLOW, |a, b| bool_to_u256(a < b)) },
+        |GT| { binary(vm, GAS_VERYLOW, |a, b| bool_to_u256(a > b)) },
+        |SLT| {
+            binary(vm, GAS_VERYLOW, |a, b| {
+                bool_to_u256(a.signed_cmp(&b) == Ordering::Less)
+            })
+        },
+        |SGT| {
+            binary(vm, GAS_VERYLOW, |a, b| {
+                bool_to_u256(a.signed_cmp(&b) == Ordering::Greater)
+            })
+        },
+        |EQ| { binary(vm, GAS_VERYLOW, |a, b| bool_to_u256(a == b)) },
+        |ISZERO| { unary(vm, GAS_VERYLOW, |a| bool_to_u256(a.is_zero())) },
+        |AND| { binary(vm, GAS_VERYLOW, |a, b| a & b) },
+        |OR| { binary(vm, GAS_VERYLOW, |a, b| a | b) },
+        |XOR| { binary(vm, GAS_VERYLOW, |a, b| a ^ b) },
+        |NOT| { unary(vm, GAS_VERYLOW, |a| !a) },
+        |BYTE| {
+            binary(vm, GAS_VERYLOW, |i, x| {
+                let i = i.to_usize().unwrap_or(32);
+                U256::from(u64::from(x.byte(i)))
+            })
+        },
+        |SHA3| { sha3(vm) },
+        |ADDRESS| {
+            vm.charge(GAS_BASE)?;
+            let address = vm.address;
+            vm.push(address.into())
+        },
+        |BALANCE| {
+            let address = Address::from(vm.pop()?);
+            vm.charge(host.schedule.balance_gas + cold_account_gas(host, &address))?;
+            vm.push(host.state.balance(&address))
+        },
+        |ORIGIN| {
+            vm.charge(GAS_BASE)?;
+            vm.push(host.tx.origin.into())
+        },
+        |CALLER| {
+            vm.charge(GAS_BASE)?;
+            let caller = vm.caller;
+            vm.push(caller.into())
+        },
+        |CALLVALUE| {
+            vm.charge(GAS_BASE)?;
+            let value = vm.value;
+            vm.push(value)
+        },
+        |CALLDATALOAD| {
+            vm.charge(GAS_VERYLOW)?;
+            let offset = vm.pop()?;
+            let word = load_word(&vm.input[..], offset);
+            vm.push(word)
+        },
+        |CALLDATASIZE| {
+            vm.charge(GAS_BASE)?;
+            let size = vm.input.len() as u64;
+            vm.push(size.into())
+        },
+        |CALLDATACOPY| {
+            vm.charge(GAS_VERYLOW)?;
+            let mem_offset = vm.pop()?;
+            let data_offset = vm.pop()?;
+            let size = vm.pop()?;
+            let (mem_offset, size) = prepare_copy(vm, mem_offset, size)?;
+            let data_offset = data_offset.to_usize().unwrap_or(usize::MAX);
+            vm.memory
+                .copy_padded(mem_offset, &vm.input[..], data_offset, size);
+            Ok(())
+        },
+        |CODESIZE| {
+            vm.charge(GAS_BASE)?;
+            let size = vm.code.len() as u64;
+            vm.push(size.into())
+        },
+        |CODECOPY| {
+            vm.charge(GAS_VERYLOW)?;
+            let mem_offset = vm.pop()?;
+            let code_offset = vm.pop()?;
+            let size = vm.pop()?;
+            let (mem_offset, size) = prepare_copy(vm, mem_offset, size)?;
+            let code_offset = code_offset.to_usize().unwrap_or(usize::MAX);
+            vm.memory
+                .copy_padded(mem_offset, &vm.code[..], code_offset, size);
+            Ok(())
+        },
+        |GASPRICE| {
+            vm.charge(GAS_BASE)?;
+            vm.push(host.tx.gas_price)
+        },
+        |EXTCODESIZE| {
+            let address = Address::from(vm.pop()?);
+            vm.charge(host.schedule.extcode_gas + cold_account_gas(host, &address))?;
+            let size = host.state.code(&address).len() as u64;
+            vm.push(size.into())
+        },
+        |EXTCODECOPY| {
+            let address = Address::from(vm.pop()?);
+            vm.charge(host.schedule.extcode_gas + cold_account_gas(host, &address))?;
+            let mem_offset = vm.pop()?;
+            let code_offset = vm.pop()?;
+            let size = vm.pop()?;
+            let (mem_offset, size) = prepare_copy(vm, mem_offset, size)?;
+            let code_offset = code_offset.to_usize().unwrap_or(usize::MAX);
+            let code = host.state.code(&address);
+            vm.memory.copy_padded(mem_offset, code, code_offset, size);
+            Ok(())
+        },
+        |RETURNDATASIZE| {
+            if !host.schedule.have_return_data {
+                return Err(Halt::InvalidOpcode(0x3d).into());
+            }
+            vm.charge(GAS_BASE)?;
+            let size = vm.return_data.len() as u64;
+            vm.push(size.into())
+        },
+        |RETURNDATACOPY| {
+            if !host.schedule.have_return_data {
+                return Err(Halt::InvalidOpcode(0x3e).into());
+            }
+            vm.charge(GAS_VERYLOW)?;
+            let mem_offset = vm.pop()?;
+            let data_offset = vm.pop()?;
+            let size = vm.pop()?;
+            let end = data_offset.checked_add(size).and_then(|end| end.to_usize());
+            match end {
+                Some(end) if end <= vm.return_data.len() => {}
+                _ => return Err(Halt::ReturnDataOutOfBounds.into()),
+            }
+            let (mem_offset, size) = prepare_copy(vm, mem_offset, size)?;
+            let data_offset = data_offset.to_usize().unwrap_or(usize::MAX);
+            vm.memory
+                .copy_padded(mem_offset, &vm.return_data[..], data_offset, size);
+            Ok(())
+        },
+        |EXTCODEHASH| {
+            if !host.schedule.have_extcodehash {
+                return Err(Halt::InvalidOpcode(0x3f).into());
+            }
+            extcodehash(vm, host)
+        },
+        |BLOCKHASH| {
+            vm.charge(GAS_BLOCKHASH)?;
+            let number = vm.pop()?;
+            vm.push(host.block.block_hash(number).into())
+        },
+        |COINBASE| {
+            vm.charge(GAS_BASE)?;
+            vm.push(host.block.coinbase.into())
+        },
+        |TIMESTAMP| {
+            vm.charge(GAS_BASE)?;
+            vm.push(host.block.timestamp.into())
+        },
+        |NUMBER| {
+            vm.charge(GAS_BASE)?;
+            vm.push(host.block.number.into())
+        },
+        |DIFFICULTY| {
+            vm.charge(GAS_BASE)?;
+            vm.push(host.block.difficulty)
+        },
+        |GASLIMIT| {
+            vm.charge(GAS_BASE)?;
+            vm.push(host.block.gas_limit.into())
+        },
+        |POP| {
+            vm.charge(GAS_BASE)?;
+            let _ = vm.stack.pop()?;
+            Ok(())
+        },
+        |MLOAD| {
+            vm.charge(GAS_VERYLOW)?;
+            let offset = vm.pop()?;
+            let (offset, _) = vm.expand_memory(offset, U256::from(32u64))?;
+            let word = U256::from_be_slice(vm.memory.get(offset, 32));
+            vm.push(word)
+        },
+        |MSTORE| {
+            vm.charge(GAS_VERYLOW)?;
+            let offset = vm.pop()?;
+            let value = vm.pop()?;
+            let (offset, _) = vm.expand_memory(offset, U256::from(32u64))?;
+            vm.memory.set(offset, &value.to_be_bytes()[..]);
+            Ok(())
+        },
+        |MSTORE8| {
+            vm.charge(GAS_VERYLOW)?;
+            let offset = vm.pop()?;
+            let value = vm.pop()?;
+            let (offset, _) = vm.expand_memory(offset, U256::ONE)?;
+            vm.memory.set_byte(offset, value.low_u64() as u8);
+            Ok(())
+        },
+        |SLOAD| {
+            let key = vm.pop()?;
+            let mut gas = host.schedule.sload_gas;
+            if host.schedule.have_access_list && host.access_storage(&vm.address, key) {
+                gas += GAS_COLD_SLOAD - GAS_WARM_STORAGE_READ;
+            }
+            vm.charge(gas)?;
+            let value = host.state.storage(&vm.address, &key);
+            vm.push(value)
+        },
+        |SSTORE| { sstore(vm, host) },
+        |JUMP| {
+            vm.charge(GAS_MID)?;
+            let dest = vm.pop()?;
+            if !vm.is_jumpdest(dest) {
+                return Err(Halt::BadJumpDestination.into());
+            }
+            vm.pc = dest.low_u64() as usize;
+            Ok(())
+        },
+        |JUMPI| {
+            vm.charge(GAS_HIGH)?;
+            let dest = vm.pop()?;
+            let condition = vm.pop()?;
+            if !condition.is_zero() {
+                if !vm.is_jumpdest(dest) {
+                    return Err(Halt::BadJumpDestination.into());
+                }
+                vm.pc = dest.low_u64() as usize;
+            }
+            Ok(())
+        },
+        |PC| {
+            vm.charge(GAS_BASE)?;
+            let pc = vm.pc as u64 - 1;
+            vm.push(pc.into())
+        },
+        |MSIZE| {
+            vm.charge(GAS_BASE)?;
+            let size = vm.memory.size() as u64;
+            vm.push(size.into())
+        },
+        |GAS| {
+            vm.charge(GAS_BASE)?;
+            let gas = vm.gas;
+            vm.push(gas.into())
+        },
+        |JUMPDEST| { vm.charge(GAS_JUMPDEST) },
+        |PUSH1| { push_n(vm, 1) },
+        |PUSH2| { push_n(vm, 2) },
+        |PUSH3| { push_n(vm, 3) },
+        |PUSH4| { push_n(vm, 4) },
+        |PUSH5| { push_n(vm, 5) },
+        |PUSH6| { push_n(vm, 6) },
+        |PUSH7| { push_n(vm, 7) },
+        |PUSH8| { push_n(vm, 8) },
+        |PUSH9| { push_n(vm, 9) },
+        |PUSH10| { push_n(vm, 10) },
+        |PUSH11| { push_n(vm, 11) },
+        |PUSH12| { push_n(vm, 12) },
+        |PUSH13| { push_n(vm, 13) },
+        |PUSH14| { push_n(vm, 14) },
+        |PUSH15| { push_n(vm, 15) },
+        |PUSH16| { push_n(vm, 16) },
+        |PUSH17| { push_n(vm, 17) },
+        |PUSH18| { push_n(vm, 18) },
+        |PUSH19| { push_n(vm, 19) },
+        |PUSH20| { push_n(vm, 20) },
+        |PUSH21| { push_n(vm, 21) },
+        |PUSH22| { push_n(vm, 22) },
+        |PUSH23| { push_n(vm, 23) },
+        |PUSH24| { push_n(vm, 24) },
+        |PUSH25| { push_n(vm, 25) },
+        |PUSH26| { push_n(vm, 26) },
+        |PUSH27| { push_n(vm, 27) },
+        |PUSH28| { push_n(vm, 28) },
+        |PUSH29| { push_n(vm, 29) },
+        |PUSH30| { push_n(vm, 30) },
+        |PUSH31| { push_n(vm, 31) },
+        |PUSH32| { push_n(vm, 32) },
+        |DUP1| { dup_n(vm, 1) },
+        |DUP2| { dup_n(vm, 2) },
+        |DUP3| { dup_n(vm, 3) },
+        |DUP4| { dup_n(vm, 4) },
+        |DUP5| { dup_n(vm, 5) },
+        |DUP6| { dup_n(vm, 6) },
+        |DUP7| { dup_n(vm, 7) },
+        |DUP8| { dup_n(vm, 8) },
+        |DUP9| { dup_n(vm, 9) },
+        |DUP10| { dup_n(vm, 10) },
+        |DUP11| { dup_n(vm, 11) },
+        |DUP12| { dup_n(vm, 12) },
+        |DUP13| { dup_n(vm, 13) },
+        |DUP14| { dup_n(vm, 14) },
+        |DUP15| { dup_n(vm, 15) },
+        |DUP16| { dup_n(vm, 16) },
+        |SWAP1| { swap_n(vm, 1) },
+        |SWAP2| { swap_n(vm, 2) },
+        |SWAP3| { swap_n(vm, 3) },
+        |SWAP4| { swap_n(vm, 4) },
+        |SWAP5| { swap_n(vm, 5) },
+        |SWAP6| { swap_n(vm, 6) },
+        |SWAP7| { swap_n(vm, 7) },
+        |SWAP8| { swap_n(vm, 8) },
+        |SWAP9| { swap_n(vm, 9) },
+        |SWAP10| { swap_n(vm, 10) },
+        |SWAP11| { swap_n(vm, 11) },
+        |SWAP12| { swap_n(vm, 12) },
+        |SWAP13| { swap_n(vm, 13) },
+        |SWAP14| { swap_n(vm, 14) },
+        |SWAP15| { swap_n(vm, 15) },
+        |SWAP16| { swap_n(vm, 16) },
+        |LOG0| { log_n(vm, host, 0) },
+        |LOG1| { log_n(vm, host, 1) },
+        |LOG2| { log_n(vm, host, 2) },
+        |LOG3| { log_n(vm, host, 3) },
+        |LOG4| { log_n(vm, host, 4) },
+        |CREATE| { create(vm, host, false) },
+        |CALL| { call(vm, host, CallKind::Call) },
+        |CALLCODE| { call(vm, host, CallKind::CallCode) },
+        |RETURN| {
+            output(vm)?;
+            Err(Halt::Return.into())
+        },
+        |REVERT| {
+            if !host.schedule.have_revert {
+                return Err(Halt::InvalidOpcode(0xfd).into());
+            }
+            output(vm)?;
+            Err(Halt::Revert.into())
+        },
+        |DELEGATECALL| {
+            if !host.schedule.have_delegatecall {
+                return Err(Halt::InvalidOpcode(0xf4).into());
+            }
+            call(vm, host, CallKind::DelegateCall)
+        },
+        |CREATE2| {
+            if !host.schedule.have_create2 {
+                return Err(Halt::InvalidOpcode(0xf5).into());
+            }
+            create(vm, host, true)
+        },
+        |STATICCALL| {
+            if !host.schedule.have_staticcall {
+                return Err(Halt::InvalidOpcode(0xfa).into());
+            }
+            call(vm, host, CallKind::StaticCall)
+        },
+        |INVALID| { Err(Halt::InvalidOpcode(0xfe).into()) },
+        |SELFDESTRUCT| {
+            vm.check_static()?;
+            let beneficiary = Address::from(vm.pop()?);
+            let address = vm.address;
+            let balance = host.state.balance(&address);
+            let creates_account = if host.schedule.no_empty {
+                !balance.is_zero() && host.state.is_dead(&beneficiary)
+            } else {
+                !host.state.exists(&beneficiary)
+            };
+            let mut gas = host.schedule.selfdestruct_gas;
+            if creates_account {
+                gas += host.schedule.selfdestruct_new_account_gas;
+            }
+            if host.schedule.have_access_list && host.access_address(&beneficiary) {
+                gas += GAS_COLD_ACCOUNT_ACCESS;
+            }
+            vm.charge(gas)?;
+            if host.add_selfdestruct(&address) {
+                host.refund += host.schedule.selfdestruct_refund;
+            }
+            host.set_balance(&address, U256::ZERO);
+            if beneficiary != address {
+                let beneficiary_balance = host.state.balance(&beneficiary);
+                host.set_balance(&beneficiary, beneficiary_balance.wrapping_add(balance));
+            }
+            Err(Halt::SelfDestruct.into())
+        },
+    ],
+    {
+        let opcode = vm.code[vm.pc - 1];
+        if host.config.eip663 {
+            ACTIONS_GROUP_EIP663[opcode as usize](vm, host)
+        } else {
+            Err(Halt::InvalidOpcode(opcode).into())
+        }
+    }
+);
+
+// The instructions of EIP-663, which are tried after the legacy ones only if they are enabled.
+ethvm_internals::create_action_groups!(
+    ACTIONS_GROUP_EIP663,
+    eof::OpKind,
+    fn(vm: &mut Interpreter, host: &mut Host) -> Result<(), Exit>,
     [
-        |STOP| {
-            println!("[Action] STOP");
+        |DUPN| {
+            vm.charge(GAS_VERYLOW)?;
+            let n = immediate_byte(vm) + 1;
+            Ok(vm.stack.dupn(n)?)
         },
-        |ADD| {
-            println!("[Action] ADD");
+        |SWAPN| {
+            vm.charge(GAS_VERYLOW)?;
+            let n = immediate_byte(vm) + 1;
+            Ok(vm.stack.swapn(n)?)
+        },
+        |EXCHANGE| {
+            vm.charge(GAS_VERYLOW)?;
+            let imm = immediate_byte(vm);
+            Ok(vm.stack.exchange((imm >> 4) + 1, (imm & 0x0f) + 1)?)
+        },
+    ],
+    { Err(Halt::InvalidOpcode(vm.code[vm.pc - 1]).into()) }
+);
+
+#[inline]
+fn inspect_log(
+    vm: &mut Interpreter,
+    host: &mut Host,
+    inspector: &mut dyn Inspector,
+    n: usize,
+) -> Result<(), Exit> {
+    log_n(vm, host, n)?;
+    if let Some(log) = host.logs.last() {
+        inspector.log(vm, log);
+    }
+    Ok(())
+}
+
+// The actions which call the hooks of the inspector, the others fall through to the default group.
+ethvm_internals::create_action_groups!(
+    ACTIONS_GROUP_INSPECTED,
+    fn(vm: &mut Interpreter, host: &mut Host, inspector: &mut dyn Inspector) -> Result<(), Exit>,
+    [
+        |LOG0| { inspect_log(vm, host, inspector, 0) },
+        |LOG1| { inspect_log(vm, host, inspector, 1) },
+        |LOG2| { inspect_log(vm, host, inspector, 2) },
+        |LOG3| { inspect_log(vm, host, inspector, 3) },
+        |LOG4| { inspect_log(vm, host, inspector, 4) },
+        |SELFDESTRUCT| {
+            let beneficiary = Address::from(U256::from_be_slice(vm.stack.peek()?));
+            let value = host.state.balance(&vm.address);
+            let ret = actions_group_default::exec_selfdestruct(vm, host);
+            if ret == Err(Exit::Halt(Halt::SelfDestruct)) {
+                inspector.selfdestruct(&vm.address, &beneficiary, value);
+            }
+            ret
         },
     ],
     {
-        println!("[Action] UNKNOWN");
+        let opcode = vm.code.get(vm.pc - 1).cloned().unwrap_or(0);
+        ACTIONS_GROUP_DEFAULT[opcode as usize](vm, host)
     }
 );
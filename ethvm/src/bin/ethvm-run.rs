@@ -0,0 +1,206 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Execute bytecode locally, and print the result.
+
+extern crate ethvm;
+extern crate serde_json;
+
+use std::{env, fs, io, process};
+
+use ethvm::env::{BlockEnv, TxEnv};
+use ethvm::host::{Host, Message};
+use ethvm::schedule::Fork;
+use ethvm::state::State;
+use ethvm::tracer::JsonTracer;
+use ethvm::types::Address;
+use ethvm::{hex, U256};
+
+const USAGE: &str = "\
+Usage: ethvm-run [OPTIONS]
+
+Execute bytecode locally, and print the result.
+
+Options:
+    --code <HEX>          The code to execute.
+    --codefile <PATH>     Read the code to execute from a file, in hex.
+    --input <HEX>         The input data (calldata).
+    --caller <ADDRESS>    The address of the caller.
+    --receiver <ADDRESS>  The address of the account which runs the code.
+    --value <VALUE>       The value to transfer, decimal or hex with the `0x` prefix.
+    --gas <GAS>           The gas limit.
+    --prestate <PATH>     The pre-state in JSON, the same format as `alloc` in a genesis file.
+    --block <PATH>        The block environment in JSON.
+    --fork <NAME>         The hard fork rules to use, up to Byzantium.
+    --trace               Write EIP-3155 traces to the standard error, as JSON lines.
+    -h, --help            Print this message.
+
+If neither `--code` nor `--codefile` is provided, the code of the receiver in the pre-state is
+executed.";
+
+struct Options {
+    code: Option<Vec<u8>>,
+    input: Vec<u8>,
+    caller: Address,
+    receiver: Address,
+    value: U256,
+    gas: u64,
+    prestate: State,
+    block: BlockEnv,
+    fork: Fork,
+    trace: bool,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        code: None,
+        input: Vec::new(),
+        // "sender" and "receiver" in ASCII, the same as geth.
+        caller: "0x000000000000000000000000000073656e646572"
+            .parse()
+            .unwrap(),
+        receiver: "0x0000000000000000000000007265636569766572"
+            .parse()
+            .unwrap(),
+        value: U256::ZERO,
+        gas: 10_000_000,
+        prestate: State::new(),
+        block: BlockEnv::default(),
+        fork: Fork::Byzantium,
+        trace: false,
+    };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            println!("{}", USAGE);
+            process::exit(0);
+        }
+        if arg == "--trace" {
+            options.trace = true;
+            continue;
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("the option `{}` requires a value", arg))?;
+        match arg.as_str() {
+            "--code" => {
+                let code =
+                    hex::decode(value.trim()).map_err(|err| format!("bad code: {:?}", err))?;
+                options.code = Some(code);
+            }
+            "--codefile" => {
+                let content = fs::read_to_string(&value)
+                    .map_err(|err| format!("failed to read {}: {}", value, err))?;
+                let code = hex::decode(content.trim())
+                    .map_err(|err| format!("bad code in {}: {:?}", value, err))?;
+                options.code = Some(code);
+            }
+            "--input" => {
+                options.input =
+                    hex::decode(value.trim()).map_err(|err| format!("bad input: {:?}", err))?;
+            }
+            "--caller" => {
+                options.caller = value
+                    .parse()
+                    .map_err(|err| format!("bad caller: {:?}", err))?;
+            }
+            "--receiver" => {
+                options.receiver = value
+                    .parse()
+                    .map_err(|err| format!("bad receiver: {:?}", err))?;
+            }
+            "--value" => {
+                options.value = value
+                    .parse()
+                    .map_err(|err| format!("bad value: {:?}", err))?;
+            }
+            "--gas" => {
+                options.gas = value
+                    .parse::<U256>()
+                    .ok()
+                    .and_then(|gas| gas.to_u64())
+                    .ok_or_else(|| format!("bad gas: {}", value))?;
+            }
+            "--prestate" => {
+                let content = fs::read_to_string(&value)
+                    .map_err(|err| format!("failed to read {}: {}", value, err))?;
+                options.prestate = serde_json::from_str(&content)
+                    .map_err(|err| format!("bad pre-state in {}: {}", value, err))?;
+            }
+            "--block" => {
+                let content = fs::read_to_string(&value)
+                    .map_err(|err| format!("failed to read {}: {}", value, err))?;
+                options.block = serde_json::from_str(&content)
+                    .map_err(|err| format!("bad block environment in {}: {}", value, err))?;
+            }
+            "--fork" => {
+                let fork: Fork = value
+                    .parse()
+                    .map_err(|_| format!("unknown fork: {}", value))?;
+                if !fork.is_supported() {
+                    return Err(format!(
+                        "unsupported fork: {}, the latest supported fork is {}",
+                        fork,
+                        Fork::LATEST_SUPPORTED
+                    ));
+                }
+                options.fork = fork;
+            }
+            _ => return Err(format!("unknown option `{}`", arg)),
+        }
+    }
+    Ok(options)
+}
+
+fn main() {
+    let options = parse_options().unwrap_or_else(|err| {
+        eprintln!("error: {}\n\n{}", err, USAGE);
+        process::exit(2);
+    });
+    let mut prestate = options.prestate;
+    if let Some(code) = options.code {
+        prestate.account_mut(&options.receiver).code = code;
+    }
+    let mut host = Host::new(options.fork, prestate.clone());
+    host.set_block_env(options.block);
+    host.set_tx_env(TxEnv {
+        origin: options.caller,
+        ..Default::default()
+    });
+    if options.trace {
+        host.set_tracer(Box::new(JsonTracer::new(io::stderr())));
+    }
+    let message = Message::new(
+        options.caller,
+        options.receiver,
+        options.value,
+        options.input,
+        options.gas,
+    );
+    let result = host.call(message);
+    let poststate = host.into_state();
+
+    println!("return data: {}", hex::encode(&result.output));
+    println!("gas used: {}", result.gas_used);
+    println!("gas refund: {}", result.gas_refund);
+    println!("halt reason: {}", result.halt);
+    println!("logs: {}", result.logs.len());
+    for log in &result.logs {
+        println!("    {}", log);
+    }
+    let diff = prestate.diff(&poststate);
+    if diff.is_empty() {
+        println!("post-state diff: none");
+    } else {
+        println!("post-state diff:");
+        print!("{}", diff);
+    }
+    if !result.halt.is_success() {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,70 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The limits of the virtual machine, which are fixed in Ethereum but could be changed for
+//! fuzzing or other chains.
+
+use host::CALL_DEPTH_LIMIT;
+use interpreter::MEMORY_LIMIT;
+use schedule::MAX_CODE_SIZE;
+use stack::{DUPN_SWAPN_LIMIT, EXCHANGE_LIMIT, STACK_LIMIT};
+
+/// The configuration of the virtual machine, the default is the same as Ethereum.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VmConfig {
+    /// The maximum number of items in the stack of a frame.
+    pub stack_limit: usize,
+    /// The maximum size of the memory of a frame, expanding the memory beyond it runs out of gas.
+    ///
+    /// Any limit is allowed, the gas costs which depend on the size of the memory saturate
+    /// instead of overflowing.
+    pub memory_limit: u64,
+    /// The maximum size of the code of new contracts, it is checked since Spurious Dragon
+    /// (EIP-170), and the init code is limited to twice of it since Shanghai (EIP-3860).
+    pub max_code_size: usize,
+    /// The maximum depth of the call stack.
+    pub call_depth_limit: usize,
+    /// The execution does not charge any gas, the gas left of a frame never changes.
+    ///
+    /// The intrinsic gas of transactions is still charged.
+    pub gas_free: bool,
+    /// `DUPN`, `SWAPN` and `EXCHANGE` are available (EIP-663), no fork activates them yet.
+    ///
+    /// Their immediate bytes are skipped as the immediate values of `PUSH*` when the `JUMPDEST`s
+    /// are analyzed, so the enabled code may have different jump destinations.
+    pub eip663: bool,
+    /// The maximum `n` of `DUPN` and `SWAPN`, the immediate values beyond it are invalid.
+    pub dupn_swapn_limit: usize,
+    /// The maximum of both `n` and `m` of `EXCHANGE`, the immediate values beyond it are invalid.
+    pub exchange_limit: usize,
+}
+
+impl ::std::default::Default for VmConfig {
+    #[inline]
+    fn default() -> Self {
+        VmConfig {
+            stack_limit: STACK_LIMIT,
+            memory_limit: MEMORY_LIMIT,
+            max_code_size: MAX_CODE_SIZE,
+            call_depth_limit: CALL_DEPTH_LIMIT,
+            gas_free: false,
+            eip663: false,
+            dupn_swapn_limit: DUPN_SWAPN_LIMIT,
+            exchange_limit: EXCHANGE_LIMIT,
+        }
+    }
+}
+
+impl VmConfig {
+    /// The maximum size of the init code (EIP-3860).
+    #[inline]
+    pub fn max_initcode_size(&self) -> usize {
+        self.max_code_size.saturating_mul(2)
+    }
+}
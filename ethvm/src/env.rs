@@ -0,0 +1,84 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The context of the block and the transaction, read by the environmental instructions.
+
+use std::collections::VecDeque;
+
+use hex;
+use transaction::AccessListItem;
+use types::{Address, H256};
+use u256::U256;
+
+/// The number of the most recent blocks whose hashes are available to `BLOCKHASH`.
+pub const BLOCK_HASH_WINDOW: usize = 256;
+
+/// The block which the execution belongs to.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockEnv {
+    #[serde(default, with = "hex::quantity")]
+    pub number: u64,
+    #[serde(default)]
+    pub coinbase: Address,
+    #[serde(default, with = "hex::quantity")]
+    pub timestamp: u64,
+    #[serde(default, with = "hex::quantity")]
+    pub gas_limit: u64,
+    /// The difficulty, or the randomness from the beacon chain after the Merge (EIP-4399).
+    #[serde(default)]
+    pub difficulty: U256,
+    /// The base fee per gas (EIP-1559).
+    #[serde(default)]
+    pub base_fee: U256,
+    /// The hashes of the most recent blocks, the last one is the hash of the parent block.
+    ///
+    /// Only the last `BLOCK_HASH_WINDOW` hashes are available.
+    #[serde(default)]
+    pub block_hashes: VecDeque<H256>,
+}
+
+impl BlockEnv {
+    /// The hash of a recent block, zero if the block is not one of the most recent
+    /// `BLOCK_HASH_WINDOW` blocks.
+    pub fn block_hash(&self, number: U256) -> H256 {
+        let number = match number.to_u64() {
+            Some(number) if number < self.number => number,
+            _ => return H256::default(),
+        };
+        let distance = (self.number - number) as usize;
+        if distance > BLOCK_HASH_WINDOW || distance > self.block_hashes.len() {
+            return H256::default();
+        }
+        self.block_hashes[self.block_hashes.len() - distance]
+    }
+
+    /// Move to the next block, the hash of the current block is pushed into the window.
+    pub fn push_block(&mut self, hash: H256) {
+        self.block_hashes.push_back(hash);
+        while self.block_hashes.len() > BLOCK_HASH_WINDOW {
+            self.block_hashes.pop_front();
+        }
+        self.number += 1;
+    }
+}
+
+/// The transaction which the execution belongs to.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxEnv {
+    /// The sender of the transaction.
+    #[serde(default)]
+    pub origin: Address,
+    /// The gas price actually paid by the sender.
+    #[serde(default)]
+    pub gas_price: U256,
+    /// The addresses and the storage slots which are accessed at the start (EIP-2930).
+    #[serde(default)]
+    pub access_list: Vec<AccessListItem>,
+}
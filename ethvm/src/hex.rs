@@ -0,0 +1,136 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Hexadecimal encoding and decoding helpers.
+
+/// Errors when decode a hexadecimal string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FromHexError {
+    BadSize,
+    BadHexAt(usize),
+}
+
+/// Encode bytes into a hexadecimal string with the `0x` prefix.
+#[inline]
+pub fn encode(bytes: &[u8]) -> String {
+    let mut ret = String::with_capacity(bytes.len() * 2 + 2);
+    ret.push_str("0x");
+    for b in bytes {
+        ret.push_str(&format!("{:02x}", b));
+    }
+    ret
+}
+
+/// Decode a hexadecimal string, the `0x` prefix is optional.
+#[inline]
+pub fn decode(s: &str) -> Result<Vec<u8>, FromHexError> {
+    let (s, offset) = if s.starts_with("0x") || s.starts_with("0X") {
+        (&s[2..], 2)
+    } else {
+        (s, 0)
+    };
+    let len = s.len();
+    if len % 2 != 0 {
+        return Err(FromHexError::BadSize);
+    }
+    let mut ret = vec![0; len / 2];
+    for (idx, chr) in s.bytes().enumerate() {
+        let val = match chr {
+            b'a'..=b'f' => chr - b'a' + 10,
+            b'A'..=b'F' => chr - b'A' + 10,
+            b'0'..=b'9' => chr - b'0',
+            _ => return Err(FromHexError::BadHexAt(idx + offset)),
+        };
+        if idx % 2 == 0 {
+            ret[idx / 2] |= val << 4;
+        } else {
+            ret[idx / 2] |= val;
+        }
+    }
+    Ok(ret)
+}
+
+/// Serialize bytes as a hexadecimal string in serde.
+pub(crate) mod bytes {
+    use std::fmt;
+
+    use serde;
+
+    pub fn serialize<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&super::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+        impl<'b> serde::de::Visitor<'b> for Visitor {
+            type Value = Vec<u8>;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a hexadecimal string")
+            }
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                super::decode(v).map_err(|err| E::custom(format!("bad hex {:?}: {:?}", v, err)))
+            }
+        }
+        deserializer.deserialize_str(Visitor)
+    }
+}
+
+/// Serialize `u64` as a hexadecimal quantity in serde, both numbers and strings are accepted
+/// when deserialize.
+pub(crate) mod quantity {
+    use std::fmt;
+
+    use serde;
+
+    use u256::U256;
+
+    pub fn serialize<S>(v: &u64, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&format!("{:#x}", v))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<u64, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+        impl<'b> serde::de::Visitor<'b> for Visitor {
+            type Value = u64;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a 64-bit unsigned integer, in a number or a string")
+            }
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(v)
+            }
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                v.parse::<U256>()
+                    .ok()
+                    .and_then(|v| v.to_u64())
+                    .ok_or_else(|| E::custom(format!("bad quantity {:?}", v)))
+            }
+        }
+        deserializer.deserialize_any(Visitor)
+    }
+}
@@ -0,0 +1,911 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The host of the interpreter, which owns the world state and collects the side effects.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use config::VmConfig;
+use env::{BlockEnv, TxEnv};
+use inspector::{Inspector, TracerInspector};
+use interpreter::{Exit, Halt, Interpreter};
+#[cfg(feature = "keccak")]
+use keccak;
+use log::Log;
+use precompiles::Precompiles;
+use schedule::{Fork, Schedule, GAS_CODE_DEPOSIT};
+use state::State;
+use tracer::Tracer;
+use transaction::{Receipt, Transaction, TransactionError};
+use types::{Address, H256};
+use u256::U256;
+
+/// The maximum depth of the call stack.
+pub const CALL_DEPTH_LIMIT: usize = 1024;
+
+/// The kinds of message calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallKind {
+    Call,
+    CallCode,
+    DelegateCall,
+    StaticCall,
+    Create,
+    /// `CREATE2` with the salt (EIP-1014).
+    Create2(H256),
+}
+
+impl CallKind {
+    /// The message creates a contract.
+    #[inline]
+    pub fn is_create(&self) -> bool {
+        matches!(*self, CallKind::Create | CallKind::Create2(_))
+    }
+}
+
+/// A message call.
+///
+/// For contract creations, `input` is the init code and `address` is the address of the new
+/// contract, which is derived by the host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub kind: CallKind,
+    pub caller: Address,
+    /// The address of the account in whose context the code is executed.
+    pub address: Address,
+    /// The address of the account whose code is executed.
+    ///
+    /// It is different from `address` for `CALLCODE` and `DELEGATECALL`.
+    pub code_address: Address,
+    /// The value of `CALLVALUE`, it is transferred only for `CallKind::Call`.
+    pub value: U256,
+    pub input: Vec<u8>,
+    /// The gas limit.
+    pub gas: u64,
+    /// The depth of the call stack, zero for the outermost call.
+    pub depth: usize,
+    /// State modifications are not allowed (EIP-214).
+    pub is_static: bool,
+}
+
+impl Message {
+    /// An outermost message call to `address`.
+    pub fn new(caller: Address, address: Address, value: U256, input: Vec<u8>, gas: u64) -> Self {
+        Message {
+            kind: CallKind::Call,
+            caller,
+            address,
+            code_address: address,
+            value,
+            input,
+            gas,
+            depth: 0,
+            is_static: false,
+        }
+    }
+
+    /// An outermost contract creation.
+    ///
+    /// Contract creation needs the `keccak` feature, it halts with `Halt::Internal` without it.
+    pub fn create(caller: Address, value: U256, init_code: Vec<u8>, gas: u64) -> Self {
+        Message {
+            kind: CallKind::Create,
+            caller,
+            address: Address::default(),
+            code_address: Address::default(),
+            value,
+            input: init_code,
+            gas,
+            depth: 0,
+            is_static: false,
+        }
+    }
+}
+
+/// The result of an execution.
+///
+/// The refund counter and the logs are only filled in the result of the outermost call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionResult {
+    pub halt: Halt,
+    /// The data returned by `RETURN` or `REVERT`.
+    pub output: Vec<u8>,
+    pub gas_used: u64,
+    pub gas_left: u64,
+    /// The refund counter, it is not applied to `gas_used` yet.
+    pub gas_refund: u64,
+    pub logs: Vec<Log>,
+    /// The address of the new contract if a contract creation succeeded.
+    pub created_address: Option<Address>,
+}
+
+impl ExecutionResult {
+    /// A result without execution.
+    #[inline]
+    fn new(halt: Halt, gas: u64, gas_left: u64) -> Self {
+        ExecutionResult {
+            halt,
+            output: Vec::new(),
+            gas_used: gas - gas_left,
+            gas_left,
+            gas_refund: 0,
+            logs: Vec::new(),
+            created_address: None,
+        }
+    }
+}
+
+/// A change of the host, which could be reverted.
+enum JournalEntry {
+    AccountCreated(Address),
+    BalanceChanged(Address, U256),
+    NonceChanged(Address, u64),
+    CodeChanged(Address, Vec<u8>),
+    StorageChanged(Address, U256, U256),
+    SelfDestructed(Address),
+    Touched(Address),
+    AddressAccessed(Address),
+    StorageAccessed(Address, U256),
+}
+
+/// The position to revert to when a frame does not succeed.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Checkpoint {
+    journal: usize,
+    logs: usize,
+    refund: u64,
+}
+
+/// A frame in the call stack.
+struct Frame {
+    message: Message,
+    vm: Box<Interpreter>,
+    checkpoint: Checkpoint,
+    /// Where to copy the output in the memory of the caller.
+    output_range: (usize, usize),
+}
+
+/// The host of the interpreter.
+pub struct Host {
+    pub(crate) schedule: Schedule,
+    pub(crate) config: VmConfig,
+    precompiles: Precompiles,
+    pub(crate) state: State,
+    pub(crate) block: BlockEnv,
+    pub(crate) tx: TxEnv,
+    pub(crate) logs: Vec<Log>,
+    pub(crate) refund: u64,
+    pub(crate) selfdestructs: BTreeSet<Address>,
+    /// The accounts which are touched, the empty ones are removed after the call (EIP-161).
+    touched: BTreeSet<Address>,
+    /// The addresses and the storage slots accessed in the current message call (EIP-2929).
+    accessed_addresses: BTreeSet<Address>,
+    accessed_storage: BTreeSet<(Address, U256)>,
+    /// The values of the storage slots before the current message call, recorded when they are
+    /// changed for the first time (EIP-2200).
+    original_storage: BTreeMap<(Address, U256), U256>,
+    journal: Vec<JournalEntry>,
+    pub(crate) inspector: Option<Box<dyn Inspector>>,
+    /// The hashes of the code of accounts, the code is not changed during a message call
+    /// except by deploying contracts.
+    #[cfg(feature = "keccak")]
+    pub(crate) code_hashes: BTreeMap<Address, H256>,
+}
+
+impl Host {
+    pub fn new(fork: Fork, state: State) -> Self {
+        Host {
+            schedule: Schedule::new(fork),
+            config: VmConfig::default(),
+            precompiles: Precompiles::new(fork),
+            state,
+            block: BlockEnv::default(),
+            tx: TxEnv::default(),
+            logs: Vec::new(),
+            refund: 0,
+            selfdestructs: BTreeSet::new(),
+            touched: BTreeSet::new(),
+            accessed_addresses: BTreeSet::new(),
+            accessed_storage: BTreeSet::new(),
+            original_storage: BTreeMap::new(),
+            journal: Vec::new(),
+            inspector: None,
+            #[cfg(feature = "keccak")]
+            code_hashes: BTreeMap::new(),
+        }
+    }
+
+    #[inline]
+    pub fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+
+    /// Replace the schedule, e.g. to change the gas costs of a fork.
+    #[inline]
+    pub fn set_schedule(&mut self, schedule: Schedule) {
+        self.schedule = schedule;
+    }
+
+    #[inline]
+    pub fn config(&self) -> &VmConfig {
+        &self.config
+    }
+
+    /// Replace the limits of the virtual machine, which apply to the frames entered later.
+    #[inline]
+    pub fn set_config(&mut self, config: VmConfig) {
+        self.config = config;
+    }
+
+    /// The precompiled contracts of the fork.
+    #[inline]
+    pub fn precompiles(&self) -> &Precompiles {
+        &self.precompiles
+    }
+
+    #[inline]
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// Modify the state directly, the changes are not journaled.
+    ///
+    /// It should not be used during a message call.
+    #[inline]
+    pub fn state_mut(&mut self) -> &mut State {
+        &mut self.state
+    }
+
+    #[inline]
+    pub fn block_env(&self) -> &BlockEnv {
+        &self.block
+    }
+
+    #[inline]
+    pub fn set_block_env(&mut self, block: BlockEnv) {
+        self.block = block;
+    }
+
+    #[inline]
+    pub fn tx_env(&self) -> &TxEnv {
+        &self.tx
+    }
+
+    /// Set the context of the transaction for `call`, it is set by `transact` automatically.
+    #[inline]
+    pub fn set_tx_env(&mut self, tx: TxEnv) {
+        self.tx = tx;
+    }
+
+    /// The refund counter of the current message call.
+    #[inline]
+    pub fn refund(&self) -> u64 {
+        self.refund
+    }
+
+    /// Install an inspector, which hooks into all following executions.
+    #[inline]
+    pub fn set_inspector(&mut self, inspector: Box<dyn Inspector>) {
+        self.inspector = Some(inspector);
+    }
+
+    #[inline]
+    pub fn take_inspector(&mut self) -> Option<Box<dyn Inspector>> {
+        self.inspector.take()
+    }
+
+    /// Install a tracer, which observes all following executions.
+    ///
+    /// It replaces the installed inspector.
+    #[inline]
+    pub fn set_tracer(&mut self, tracer: Box<dyn Tracer>) {
+        self.inspector = Some(Box::new(TracerInspector::new(tracer)));
+    }
+
+    #[inline]
+    pub fn into_state(self) -> State {
+        self.state
+    }
+
+    /// The hash of the code of an account, it is cached during a message call.
+    #[cfg(feature = "keccak")]
+    pub(crate) fn code_hash(&mut self, address: &Address) -> H256 {
+        let state = &self.state;
+        *self
+            .code_hashes
+            .entry(*address)
+            .or_insert_with(|| keccak::keccak256(state.code(address)))
+    }
+
+    /// Get an account for modification, record the creation if it does not exist.
+    fn account_mut(&mut self, address: &Address) -> &mut ::state::Account {
+        if !self.state.exists(address) {
+            self.journal.push(JournalEntry::AccountCreated(*address));
+        }
+        self.state.account_mut(address)
+    }
+
+    /// Mark an account as touched, the account is created if not exist.
+    pub(crate) fn touch(&mut self, address: &Address) {
+        self.account_mut(address);
+        if self.touched.insert(*address) {
+            self.journal.push(JournalEntry::Touched(*address));
+        }
+    }
+
+    pub(crate) fn set_balance(&mut self, address: &Address, balance: U256) {
+        self.touch(address);
+        let account = self.state.account_mut(address);
+        let old = ::std::mem::replace(&mut account.balance, balance);
+        self.journal
+            .push(JournalEntry::BalanceChanged(*address, old));
+    }
+
+    pub(crate) fn set_nonce(&mut self, address: &Address, nonce: u64) {
+        let account = self.account_mut(address);
+        let old = ::std::mem::replace(&mut account.nonce, nonce);
+        self.journal.push(JournalEntry::NonceChanged(*address, old));
+    }
+
+    pub(crate) fn set_code(&mut self, address: &Address, code: Vec<u8>) {
+        let account = self.account_mut(address);
+        let old = ::std::mem::replace(&mut account.code, code);
+        self.journal.push(JournalEntry::CodeChanged(*address, old));
+        #[cfg(feature = "keccak")]
+        self.code_hashes.remove(address);
+    }
+
+    pub(crate) fn set_storage(&mut self, address: &Address, key: U256, value: U256) {
+        let old = self.state.storage(address, &key);
+        if old == value {
+            return;
+        }
+        self.original_storage.entry((*address, key)).or_insert(old);
+        self.account_mut(address);
+        self.state.set_storage(address, key, value);
+        self.journal
+            .push(JournalEntry::StorageChanged(*address, key, old));
+    }
+
+    /// The value of a storage slot before the current message call.
+    pub(crate) fn original_storage(&self, address: &Address, key: &U256) -> U256 {
+        match self.original_storage.get(&(*address, *key)) {
+            Some(value) => *value,
+            None => self.state.storage(address, key),
+        }
+    }
+
+    /// Transfer `value` from `from` to `to`, return `false` if the balance is insufficient.
+    ///
+    /// Both accounts are touched.
+    pub(crate) fn transfer(&mut self, from: &Address, to: &Address, value: U256) -> bool {
+        let balance = self.state.balance(from);
+        if balance < value {
+            return false;
+        }
+        self.set_balance(from, balance.wrapping_sub(value));
+        let balance = self.state.balance(to);
+        self.set_balance(to, balance.wrapping_add(value));
+        true
+    }
+
+    /// Record a self-destructed account, return `false` if it is already recorded.
+    pub(crate) fn add_selfdestruct(&mut self, address: &Address) -> bool {
+        if self.selfdestructs.insert(*address) {
+            self.journal.push(JournalEntry::SelfDestructed(*address));
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Mark an address as accessed, return `true` if it was not accessed before.
+    pub(crate) fn access_address(&mut self, address: &Address) -> bool {
+        if self.accessed_addresses.insert(*address) {
+            self.journal.push(JournalEntry::AddressAccessed(*address));
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Mark a storage slot as accessed, return `true` if it was not accessed before.
+    pub(crate) fn access_storage(&mut self, address: &Address, key: U256) -> bool {
+        if self.accessed_storage.insert((*address, key)) {
+            self.journal
+                .push(JournalEntry::StorageAccessed(*address, key));
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Access the accounts and the storage slots which are warm at the start of a message call.
+    fn warm_up(&mut self, message: &Message) {
+        self.accessed_addresses.insert(message.caller);
+        if !message.kind.is_create() {
+            self.accessed_addresses.insert(message.address);
+        }
+        self.accessed_addresses.extend(self.precompiles.addresses());
+        for item in &self.tx.access_list {
+            self.accessed_addresses.insert(item.address);
+            for key in &item.storage_keys {
+                self.accessed_storage
+                    .insert((item.address, U256::from(*key)));
+            }
+        }
+        if self.schedule.warm_coinbase {
+            self.accessed_addresses.insert(self.block.coinbase);
+        }
+    }
+
+    pub(crate) fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            journal: self.journal.len(),
+            logs: self.logs.len(),
+            refund: self.refund,
+        }
+    }
+
+    /// Revert all changes since the checkpoint.
+    pub(crate) fn revert_to(&mut self, checkpoint: Checkpoint) {
+        while self.journal.len() > checkpoint.journal {
+            let entry = self.journal.pop().expect("the journal should not be empty");
+            match entry {
+                JournalEntry::AccountCreated(address) => {
+                    self.state.remove(&address);
+                }
+                JournalEntry::BalanceChanged(address, balance) => {
+                    self.state.account_mut(&address).balance = balance;
+                }
+                JournalEntry::NonceChanged(address, nonce) => {
+                    self.state.account_mut(&address).nonce = nonce;
+                }
+                JournalEntry::CodeChanged(address, code) => {
+                    self.state.account_mut(&address).code = code;
+                    #[cfg(feature = "keccak")]
+                    self.code_hashes.remove(&address);
+                }
+                JournalEntry::StorageChanged(address, key, value) => {
+                    self.state.set_storage(&address, key, value);
+                }
+                JournalEntry::SelfDestructed(address) => {
+                    self.selfdestructs.remove(&address);
+                }
+                JournalEntry::Touched(address) => {
+                    // The touch of RIPEMD-160 is kept, as it was in the main network at the
+                    // block 2675119.
+                    if address != U256::from(3).into() {
+                        self.touched.remove(&address);
+                    }
+                }
+                JournalEntry::AddressAccessed(address) => {
+                    self.accessed_addresses.remove(&address);
+                }
+                JournalEntry::StorageAccessed(address, key) => {
+                    self.accessed_storage.remove(&(address, key));
+                }
+            }
+        }
+        self.logs.truncate(checkpoint.logs);
+        self.refund = checkpoint.refund;
+    }
+
+    /// Execute a message call or a contract creation, all changes are discarded if it does not
+    /// succeed.
+    pub fn call(&mut self, message: Message) -> ExecutionResult {
+        self.logs.clear();
+        self.refund = 0;
+        self.selfdestructs.clear();
+        self.touched.clear();
+        self.accessed_addresses.clear();
+        self.accessed_storage.clear();
+        self.original_storage.clear();
+        self.journal.clear();
+        if self.schedule.have_access_list {
+            self.warm_up(&message);
+        }
+        // The state could have been changed through `state_mut` since the last call.
+        #[cfg(feature = "keccak")]
+        self.code_hashes.clear();
+        let mut inspector = self.inspector.take();
+        let mut result = match inspector {
+            Some(ref mut inspector) => self.execute(message, Some(&mut **inspector)),
+            None => self.execute(message, None),
+        };
+        self.inspector = inspector;
+        if result.halt.is_success() {
+            for address in &self.selfdestructs {
+                self.state.remove(address);
+            }
+        }
+        if self.schedule.no_empty {
+            for address in &self.touched {
+                if self.state.is_dead(address) {
+                    self.state.remove(address);
+                }
+            }
+        }
+        self.journal.clear();
+        result.gas_refund = self.refund;
+        result.logs = self.logs.split_off(0);
+        if let Some(mut inspector) = self.inspector.take() {
+            inspector.end(self, &result);
+            self.inspector = Some(inspector);
+        }
+        result
+    }
+
+    /// Execute a transaction, the state is not changed if the transaction is invalid.
+    ///
+    /// The sender buys all gas before the execution, then the gas left and the refund are
+    /// returned to the sender, and the fee is paid to the coinbase of the block environment.
+    /// The base fee is burnt since London.
+    pub fn transact(&mut self, tx: &Transaction) -> Result<Receipt, TransactionError> {
+        let base_fee = self.block.base_fee;
+        let gas_price = self.validate(tx, base_fee)?;
+        self.tx = TxEnv {
+            origin: tx.sender,
+            gas_price,
+            access_list: tx.access_list.clone(),
+        };
+        // The cost of the gas limit is checked, so the arithmetic of the fees can not overflow.
+        let sender = self.state.account_mut(&tx.sender);
+        sender.balance = sender
+            .balance
+            .wrapping_sub(U256::from(tx.gas_limit).wrapping_mul(gas_price));
+        let gas = tx.gas_limit - tx.intrinsic_gas(&self.schedule);
+        let message = match tx.to {
+            Some(to) => {
+                sender.nonce += 1;
+                Message::new(tx.sender, to, tx.value, tx.data.clone(), gas)
+            }
+            // The nonce is bumped by the creation.
+            None => Message::create(tx.sender, tx.value, tx.data.clone(), gas),
+        };
+        let result = self.call(message);
+        let gas_used = tx.gas_limit - result.gas_left;
+        let gas_refunded = ::std::cmp::min(
+            result.gas_refund,
+            gas_used / self.schedule.max_refund_quotient,
+        );
+        let gas_used = gas_used - gas_refunded;
+        let sender = self.state.account_mut(&tx.sender);
+        sender.balance = sender
+            .balance
+            .wrapping_add(U256::from(tx.gas_limit - gas_used).wrapping_mul(gas_price));
+        let fee_per_gas = if self.schedule.have_base_fee {
+            gas_price.wrapping_sub(base_fee)
+        } else {
+            gas_price
+        };
+        let coinbase = self.block.coinbase;
+        let account = self.state.account_mut(&coinbase);
+        account.balance = account
+            .balance
+            .wrapping_add(U256::from(gas_used).wrapping_mul(fee_per_gas));
+        if self.schedule.no_empty && self.state.is_dead(&coinbase) {
+            self.state.remove(&coinbase);
+        }
+        Ok(Receipt {
+            halt: result.halt,
+            output: result.output,
+            gas_used,
+            gas_refunded,
+            logs: result.logs,
+            contract_address: result.created_address,
+        })
+    }
+
+    /// Check a transaction, and return the gas price paid by the sender.
+    fn validate(&self, tx: &Transaction, base_fee: U256) -> Result<U256, TransactionError> {
+        let nonce = self.state.nonce(&tx.sender);
+        if tx.nonce != nonce {
+            return Err(TransactionError::NonceMismatch {
+                expected: nonce,
+                got: tx.nonce,
+            });
+        }
+        if nonce == u64::MAX {
+            return Err(TransactionError::NonceOverflow);
+        }
+        if !self.state.code(&tx.sender).is_empty() {
+            return Err(TransactionError::SenderNotEoa);
+        }
+        if !tx.access_list.is_empty() && !self.schedule.have_access_list {
+            return Err(TransactionError::UnsupportedType);
+        }
+        let gas_price = match tx.max_priority_fee_per_gas {
+            Some(priority_fee) => {
+                if !self.schedule.have_base_fee {
+                    return Err(TransactionError::UnsupportedType);
+                }
+                if priority_fee > tx.gas_price {
+                    return Err(TransactionError::PriorityFeeTooHigh);
+                }
+                base_fee
+                    .checked_add(priority_fee)
+                    .map_or(tx.gas_price, |price| ::std::cmp::min(price, tx.gas_price))
+            }
+            None => tx.gas_price,
+        };
+        if self.schedule.have_base_fee && tx.gas_price < base_fee {
+            return Err(TransactionError::FeeCapTooLow);
+        }
+        let intrinsic = tx.intrinsic_gas(&self.schedule);
+        if tx.gas_limit < intrinsic {
+            return Err(TransactionError::IntrinsicGasTooLow {
+                intrinsic,
+                limit: tx.gas_limit,
+            });
+        }
+        if tx.is_create()
+            && self.schedule.limit_initcode_size
+            && tx.data.len() > self.config.max_initcode_size()
+        {
+            return Err(TransactionError::InitCodeSizeExceeded);
+        }
+        // The balance should cover the maximum fee, not only the fee actually paid.
+        let cost = U256::from(tx.gas_limit)
+            .checked_mul(tx.gas_price)
+            .and_then(|cost| cost.checked_add(tx.value));
+        match cost {
+            Some(cost) if cost <= self.state.balance(&tx.sender) => Ok(gas_price),
+            _ => Err(TransactionError::InsufficientFunds),
+        }
+    }
+
+    /// Execute the frames iteratively until the outermost one halts.
+    fn execute(
+        &mut self,
+        message: Message,
+        mut inspector: Option<&mut dyn Inspector>,
+    ) -> ExecutionResult {
+        let mut frames: Vec<Frame> = Vec::new();
+        let mut next = Some((message, (0, 0)));
+        loop {
+            let (message, output_range, mut result) = match next.take() {
+                Some((mut message, output_range)) => {
+                    if message.kind.is_create() {
+                        if let Err(halt) = self.prepare_create(&mut message) {
+                            // The creation fails before it starts, the inspector does not see it.
+                            let result = ExecutionResult::new(halt, message.gas, message.gas);
+                            match frames.last_mut() {
+                                Some(frame) => {
+                                    frame.vm.resume(&message, output_range, result);
+                                    continue;
+                                }
+                                None => return result,
+                            }
+                        }
+                    }
+                    let hooked = match inspector {
+                        Some(ref mut inspector) if message.kind.is_create() => {
+                            inspector.create(self, &mut message)
+                        }
+                        Some(ref mut inspector) => inspector.call(self, &mut message),
+                        None => None,
+                    };
+                    let result = match hooked {
+                        Some(result) => result,
+                        None => match self.enter(&message) {
+                            Ok((vm, checkpoint)) => {
+                                frames.push(Frame {
+                                    message,
+                                    vm,
+                                    checkpoint,
+                                    output_range,
+                                });
+                                continue;
+                            }
+                            Err(result) => result,
+                        },
+                    };
+                    (message, output_range, result)
+                }
+                None => {
+                    let exit = {
+                        let frame = frames.last_mut().expect("there is at least one frame");
+                        match inspector {
+                            Some(ref mut inspector) => {
+                                frame.vm.run_inspected(self, &mut **inspector)
+                            }
+                            None => frame.vm.run_plain(self),
+                        }
+                    };
+                    match exit {
+                        Exit::Halt(halt) => {
+                            let frame = frames.pop().expect("there is at least one frame");
+                            let result =
+                                self.leave(frame.vm, frame.checkpoint, &frame.message, halt);
+                            (frame.message, frame.output_range, result)
+                        }
+                        Exit::Call(request) => {
+                            let request = *request;
+                            next = Some((request.message, request.output_range));
+                            continue;
+                        }
+                        Exit::Stack(error) => {
+                            unreachable!("the interpreter halts for {}", error)
+                        }
+                    }
+                }
+            };
+            if let Some(ref mut inspector) = inspector {
+                if message.kind.is_create() {
+                    inspector.create_end(self, &message, &mut result);
+                } else {
+                    inspector.call_end(self, &message, &mut result);
+                }
+            }
+            match frames.last_mut() {
+                Some(frame) => frame.vm.resume(&message, output_range, result),
+                None => return result,
+            }
+        }
+    }
+
+    /// Bump the nonce of the creator and derive the address of the new contract.
+    ///
+    /// The nonce is bumped even if the creation fails later.
+    #[cfg(feature = "keccak")]
+    fn prepare_create(&mut self, message: &mut Message) -> Result<(), Halt> {
+        let nonce = self.state.nonce(&message.caller);
+        let next_nonce = nonce.checked_add(1).ok_or(Halt::NonceOverflow)?;
+        self.set_nonce(&message.caller, next_nonce);
+        let address = match message.kind {
+            CallKind::Create2(ref salt) => {
+                Address::from_create2(&message.caller, salt, &message.input[..])
+            }
+            _ => Address::from_create(&message.caller, nonce),
+        };
+        message.address = address;
+        message.code_address = address;
+        if self.schedule.have_access_list {
+            self.access_address(&address);
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "keccak"))]
+    fn prepare_create(&mut self, _message: &mut Message) -> Result<(), Halt> {
+        Err(Halt::Internal)
+    }
+
+    /// Prepare a frame, or return the result directly if there is no code to execute.
+    fn enter(
+        &mut self,
+        message: &Message,
+    ) -> Result<(Box<Interpreter>, Checkpoint), ExecutionResult> {
+        let is_create = message.kind.is_create();
+        if is_create {
+            // An account with nonce, code or storage can not be replaced (EIP-684, EIP-7610).
+            let collision = self.state.account(&message.address).is_some_and(|account| {
+                account.nonce != 0 || !account.code.is_empty() || !account.storage.is_empty()
+            });
+            if collision {
+                return Err(ExecutionResult::new(Halt::CreateCollision, message.gas, 0));
+            }
+        }
+        let checkpoint = self.checkpoint();
+        if is_create {
+            self.touch(&message.address);
+            if self.schedule.no_empty {
+                self.set_nonce(&message.address, 1);
+            }
+        }
+        // The value of `CALLCODE` and `DELEGATECALL` stays in the account.
+        let transfers = !matches!(message.kind, CallKind::CallCode | CallKind::DelegateCall);
+        if transfers && !self.transfer(&message.caller, &message.address, message.value) {
+            self.revert_to(checkpoint);
+            return Err(ExecutionResult::new(
+                Halt::InsufficientBalance,
+                message.gas,
+                message.gas,
+            ));
+        }
+        if !is_create {
+            if let Some(precompile) = self.precompiles.get(&message.code_address).cloned() {
+                // Without gas, the precompile can not run out of gas and the gas is kept.
+                let gas = if self.config.gas_free {
+                    u64::MAX
+                } else {
+                    message.gas
+                };
+                let result = match precompile.execute(&message.input[..], gas) {
+                    Ok((output, gas_left)) => {
+                        let gas_left = if self.config.gas_free {
+                            message.gas
+                        } else {
+                            gas_left
+                        };
+                        let mut result = ExecutionResult::new(Halt::Return, message.gas, gas_left);
+                        result.output = output;
+                        result
+                    }
+                    Err(halt) => {
+                        self.revert_to(checkpoint);
+                        ExecutionResult::new(halt, message.gas, 0)
+                    }
+                };
+                return Err(result);
+            }
+        }
+        let code = if is_create {
+            message.input.clone()
+        } else {
+            self.state.code(&message.code_address).to_vec()
+        };
+        if code.is_empty() {
+            let mut result = ExecutionResult::new(Halt::Stop, message.gas, message.gas);
+            if is_create {
+                result.created_address = Some(message.address);
+            }
+            return Err(result);
+        }
+        let vm = Box::new(Interpreter::new(message, code, &self.config));
+        Ok((vm, checkpoint))
+    }
+
+    /// Finish a frame, and revert its changes if it does not succeed.
+    fn leave(
+        &mut self,
+        mut vm: Box<Interpreter>,
+        checkpoint: Checkpoint,
+        message: &Message,
+        halt: Halt,
+    ) -> ExecutionResult {
+        let mut halt = halt;
+        let mut created_address = None;
+        if message.kind.is_create() && halt.is_success() {
+            match self.deposit_code(&mut vm, &message.address) {
+                Ok(()) => created_address = Some(message.address),
+                Err(err) => halt = err,
+            }
+        }
+        if !halt.is_success() {
+            self.revert_to(checkpoint);
+        }
+        let gas_left = if halt.is_error() { 0 } else { vm.gas };
+        ExecutionResult {
+            halt,
+            output: vm.output,
+            gas_used: message.gas - gas_left,
+            gas_left,
+            gas_refund: 0,
+            logs: Vec::new(),
+            created_address,
+        }
+    }
+
+    /// Store the output of the init code as the code of the new contract.
+    fn deposit_code(&mut self, vm: &mut Interpreter, address: &Address) -> Result<(), Halt> {
+        if self.schedule.limit_code_size && vm.output.len() > self.config.max_code_size {
+            return Err(Halt::CodeSizeExceeded);
+        }
+        if self.schedule.reject_ef_code && vm.output.first() == Some(&0xef) {
+            return Err(Halt::InvalidCodePrefix);
+        }
+        let gas = if self.config.gas_free {
+            0
+        } else {
+            GAS_CODE_DEPOSIT * vm.output.len() as u64
+        };
+        if vm.gas < gas {
+            // Before Homestead, the contract is left without code.
+            return if self.schedule.fail_on_code_deposit {
+                Err(Halt::CodeStoreOutOfGas)
+            } else {
+                Ok(())
+            };
+        }
+        vm.gas -= gas;
+        self.set_code(address, vm.output.clone());
+        Ok(())
+    }
+}
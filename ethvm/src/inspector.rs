@@ -0,0 +1,91 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Inspectors, which hook into the execution and could change it.
+
+use host::{ExecutionResult, Host, Message};
+use interpreter::{Halt, Interpreter};
+use log::Log;
+use tracer::Tracer;
+use types::Address;
+use u256::U256;
+
+/// Callbacks of the execution.
+///
+/// All methods do nothing by default. An inspector is installed into the host by
+/// [`Host::set_inspector`], the interpreter switches to a separate loop and action group when
+/// an inspector is installed, so the execution without inspectors pays nothing for the hooks.
+///
+/// [`Host::set_inspector`]: ../host/struct.Host.html#method.set_inspector
+pub trait Inspector {
+    /// Called before each step, `vm.pc()` points to the opcode which is going to be executed.
+    ///
+    /// Return an error to halt the frame without executing the step.
+    fn step(&mut self, _vm: &mut Interpreter, _host: &mut Host) -> Result<(), Halt> {
+        Ok(())
+    }
+
+    /// Called after each step, `halt` is `Some` if the step halted the frame.
+    fn step_end(&mut self, _vm: &mut Interpreter, _host: &mut Host, _halt: Option<Halt>) {}
+
+    /// Called before a message call is executed.
+    ///
+    /// Return a result to skip the execution, and the result is used instead.
+    fn call(&mut self, _host: &mut Host, _message: &mut Message) -> Option<ExecutionResult> {
+        None
+    }
+
+    /// Called after a message call, the result could be changed.
+    fn call_end(&mut self, _host: &mut Host, _message: &Message, _result: &mut ExecutionResult) {}
+
+    /// Called before a contract creation is executed, `message.input` is the init code and
+    /// `message.address` is the address of the new contract.
+    ///
+    /// Return a result to skip the execution, and the result is used instead.
+    fn create(&mut self, _host: &mut Host, _message: &mut Message) -> Option<ExecutionResult> {
+        None
+    }
+
+    /// Called after a contract creation, the result could be changed.
+    fn create_end(&mut self, _host: &mut Host, _message: &Message, _result: &mut ExecutionResult) {}
+
+    /// Called when a log is emitted.
+    fn log(&mut self, _vm: &Interpreter, _log: &Log) {}
+
+    /// Called when a contract self-destructs, `value` is the balance sent to the beneficiary.
+    fn selfdestruct(&mut self, _address: &Address, _beneficiary: &Address, _value: U256) {}
+
+    /// Called when `Host::call` is finished, after the empty accounts touched by it are removed.
+    fn end(&mut self, _host: &Host, _result: &ExecutionResult) {}
+}
+
+/// Run a tracer as an inspector.
+pub(crate) struct TracerInspector {
+    tracer: Box<dyn Tracer>,
+}
+
+impl TracerInspector {
+    pub(crate) fn new(tracer: Box<dyn Tracer>) -> Self {
+        TracerInspector { tracer }
+    }
+}
+
+impl Inspector for TracerInspector {
+    fn step(&mut self, vm: &mut Interpreter, host: &mut Host) -> Result<(), Halt> {
+        self.tracer.step(vm, host);
+        Ok(())
+    }
+
+    fn step_end(&mut self, vm: &mut Interpreter, host: &mut Host, halt: Option<Halt>) {
+        self.tracer.step_end(vm, host, halt);
+    }
+
+    fn end(&mut self, host: &Host, result: &ExecutionResult) {
+        self.tracer.end(host, result);
+    }
+}
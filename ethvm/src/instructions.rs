@@ -0,0 +1,78 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Iterate over the instructions of bytecode without decoding them into `OpCode`s.
+
+use std::iter::FusedIterator;
+
+use opcodes;
+
+/// An iterator over the instructions of bytecode, yields `(pc, opcode, immediate)`.
+///
+/// The immediate values are borrowed from the bytecode, nothing is allocated. The immediate of
+/// the last instruction is shorter than the size of its kind if the bytecode is truncated, and
+/// the immediate of an unknown opcode is empty.
+///
+/// `DUPN`, `SWAPN` and `EXCHANGE` are unknown opcodes unless the iterator is created by
+/// `with_eip663`, as the jump destinations are analyzed with `VmConfig::eip663`.
+#[derive(Debug, Clone)]
+pub struct Instructions<'a> {
+    code: &'a [u8],
+    pc: usize,
+    eip663: bool,
+}
+
+impl<'a> Instructions<'a> {
+    #[inline]
+    pub fn new(code: &'a [u8]) -> Self {
+        Instructions {
+            code,
+            pc: 0,
+            eip663: false,
+        }
+    }
+
+    /// Iterate over the instructions with `DUPN`, `SWAPN` and `EXCHANGE` (EIP-663).
+    #[inline]
+    pub fn with_eip663(code: &'a [u8]) -> Self {
+        Instructions {
+            code,
+            pc: 0,
+            eip663: true,
+        }
+    }
+
+    /// The position of the next instruction.
+    #[inline]
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+}
+
+impl<'a> Iterator for Instructions<'a> {
+    type Item = (usize, u8, &'a [u8]);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let pc = self.pc;
+        let opcode = *self.code.get(pc)?;
+        let size = opcodes::immediate_size(opcode, self.eip663);
+        let start = pc + 1;
+        let end = ::std::cmp::min(start + size, self.code.len());
+        self.pc = end;
+        Some((pc, opcode, &self.code[start..end]))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.code.len() - self.pc;
+        (remaining.div_ceil(33), Some(remaining))
+    }
+}
+
+impl<'a> FusedIterator for Instructions<'a> {}
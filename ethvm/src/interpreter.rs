@@ -0,0 +1,471 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The interpreter which executes the code of a single frame.
+
+use std::fmt;
+
+use actions::{ACTIONS_GROUP_DEFAULT, ACTIONS_GROUP_INSPECTED};
+use config::VmConfig;
+use host::{ExecutionResult, Host, Message};
+use inspector::Inspector;
+use memory::Memory;
+use opcodes::{self, OpKind};
+use schedule;
+use stack::{Stack, StackError};
+use types::Address;
+use u256::U256;
+
+/// The maximum size of the memory by default, expand the memory beyond it always runs out of gas.
+pub const MEMORY_LIMIT: u64 = 0xffff_ffff;
+
+/// Why the execution halted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Halt {
+    Stop,
+    Return,
+    Revert,
+    SelfDestruct,
+    OutOfGas,
+    StackUnderflow,
+    StackOverflow,
+    BadJumpDestination,
+    InvalidOpcode(u8),
+    /// The immediate value of an instruction is out of its range.
+    InvalidArgument,
+    InsufficientBalance,
+    /// Modify the state in a static call.
+    StaticModeViolation,
+    /// Copy the return data out of its range.
+    ReturnDataOutOfBounds,
+    /// Create a contract at an address which is already used.
+    CreateCollision,
+    /// The code of the new contract is larger than the limit (EIP-170).
+    CodeSizeExceeded,
+    /// The code of the new contract starts with `0xef` (EIP-3541).
+    InvalidCodePrefix,
+    /// Not enough gas to store the code of the new contract.
+    CodeStoreOutOfGas,
+    /// The init code is larger than the limit (EIP-3860).
+    InitCodeSizeExceeded,
+    /// The nonce of the creator can not be increased.
+    NonceOverflow,
+    /// The input of a precompiled contract is invalid.
+    PrecompileFailed,
+    /// An invariant of the interpreter was broken.
+    Internal,
+}
+
+impl Halt {
+    /// The execution halted normally, and all changes should be kept.
+    #[inline]
+    pub fn is_success(&self) -> bool {
+        matches!(*self, Halt::Stop | Halt::Return | Halt::SelfDestruct)
+    }
+
+    /// The execution was reverted, the changes should be discarded but the gas left is returned.
+    #[inline]
+    pub fn is_revert(&self) -> bool {
+        *self == Halt::Revert
+    }
+
+    /// The execution halted exceptionally, all gas is consumed.
+    #[inline]
+    pub fn is_error(&self) -> bool {
+        !self.is_success() && !self.is_revert()
+    }
+}
+
+impl fmt::Display for Halt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Halt::Stop => write!(f, "stop"),
+            Halt::Return => write!(f, "return"),
+            Halt::Revert => write!(f, "execution reverted"),
+            Halt::SelfDestruct => write!(f, "self-destruct"),
+            Halt::OutOfGas => write!(f, "out of gas"),
+            Halt::StackUnderflow => write!(f, "stack underflow"),
+            Halt::StackOverflow => write!(f, "stack overflow"),
+            Halt::BadJumpDestination => write!(f, "invalid jump destination"),
+            Halt::InvalidOpcode(v) => write!(f, "invalid opcode {:#04x}", v),
+            Halt::InvalidArgument => write!(f, "invalid instruction argument"),
+            Halt::InsufficientBalance => write!(f, "insufficient balance for transfer"),
+            Halt::StaticModeViolation => write!(f, "write protection"),
+            Halt::ReturnDataOutOfBounds => write!(f, "return data out of bounds"),
+            Halt::CreateCollision => write!(f, "contract address collision"),
+            Halt::CodeSizeExceeded => write!(f, "max code size exceeded"),
+            Halt::InvalidCodePrefix => write!(f, "invalid code: must not begin with 0xef"),
+            Halt::CodeStoreOutOfGas => write!(f, "contract creation code storage out of gas"),
+            Halt::InitCodeSizeExceeded => write!(f, "max initcode size exceeded"),
+            Halt::NonceOverflow => write!(f, "nonce uint64 overflow"),
+            Halt::PrecompileFailed => write!(f, "precompiled contract failed"),
+            Halt::Internal => write!(f, "internal error"),
+        }
+    }
+}
+
+impl ::std::convert::From<StackError> for Halt {
+    #[inline]
+    fn from(err: StackError) -> Self {
+        match err {
+            StackError::Underflow { .. } => Halt::StackUnderflow,
+            StackError::Overflow { .. } => Halt::StackOverflow,
+            StackError::InvalidArgument { .. } => Halt::InvalidArgument,
+        }
+    }
+}
+
+/// A stack error raised by an instruction, with where the instruction is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StackFault {
+    pub pc: usize,
+    pub opcode: u8,
+    pub error: StackError,
+}
+
+impl fmt::Display for StackFault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at pc {} ", self.error, self.pc)?;
+        // Only the executed instructions fault, so the ones of EIP-663 are enabled.
+        match opcodes::mnemonic(self.opcode, true) {
+            Some(mnemonic) => write!(f, "({})", mnemonic),
+            None => write!(f, "(opcode {:#04x})", self.opcode),
+        }
+    }
+}
+
+impl ::std::error::Error for StackFault {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Why an action stops the interpreter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Exit {
+    /// The frame halted.
+    Halt(Halt),
+    /// The frame is suspended to execute a sub-call, it is resumed with the result.
+    Call(Box<CallRequest>),
+    /// A stack operation failed, the interpreter records it as a `StackFault` and halts the
+    /// frame with the corresponding `Halt`.
+    Stack(StackError),
+}
+
+impl ::std::convert::From<Halt> for Exit {
+    #[inline]
+    fn from(halt: Halt) -> Self {
+        Exit::Halt(halt)
+    }
+}
+
+impl ::std::convert::From<StackError> for Exit {
+    #[inline]
+    fn from(err: StackError) -> Self {
+        Exit::Stack(err)
+    }
+}
+
+/// A sub-call requested by the call instructions, or a contract creation requested by `CREATE`
+/// and `CREATE2`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallRequest {
+    pub message: Message,
+    /// The range of the memory of the caller to write the output, as `(offset, size)`.
+    ///
+    /// It is empty for contract creations.
+    pub output_range: (usize, usize),
+}
+
+/// A frame of execution: the code with its own stack, memory and program counter.
+pub struct Interpreter {
+    pub(crate) address: Address,
+    pub(crate) caller: Address,
+    pub(crate) value: U256,
+    pub(crate) input: Vec<u8>,
+    pub(crate) code: Vec<u8>,
+    jumpdests: Vec<bool>,
+    pub(crate) pc: usize,
+    pub(crate) stack: Stack,
+    pub(crate) memory: Memory,
+    pub(crate) gas: u64,
+    pub(crate) output: Vec<u8>,
+    /// The output of the last sub-call.
+    pub(crate) return_data: Vec<u8>,
+    pub(crate) depth: usize,
+    pub(crate) is_static: bool,
+    memory_limit: u64,
+    gas_free: bool,
+    stack_fault: Option<StackFault>,
+}
+
+impl Interpreter {
+    pub(crate) fn new(message: &Message, code: Vec<u8>, config: &VmConfig) -> Self {
+        let jumpdests = analyze_jumpdests(&code[..], config.eip663);
+        let mut stack = Stack::with_limit(config.stack_limit);
+        stack.set_dupn_swapn_limit(config.dupn_swapn_limit);
+        stack.set_exchange_limit(config.exchange_limit);
+        Interpreter {
+            address: message.address,
+            caller: message.caller,
+            value: message.value,
+            // The init code is the code, not the input.
+            input: if message.kind.is_create() {
+                Vec::new()
+            } else {
+                message.input.clone()
+            },
+            code,
+            jumpdests,
+            pc: 0,
+            stack,
+            memory: Memory::default(),
+            gas: message.gas,
+            output: Vec::new(),
+            return_data: Vec::new(),
+            depth: message.depth,
+            is_static: message.is_static,
+            memory_limit: config.memory_limit,
+            gas_free: config.gas_free,
+            stack_fault: None,
+        }
+    }
+
+    /// Run until halt or a sub-call.
+    pub(crate) fn run_plain(&mut self, host: &mut Host) -> Exit {
+        loop {
+            // Running out of the code is the same as `STOP`.
+            let pc = self.pc;
+            let opcode = self.code.get(pc).cloned().unwrap_or(0);
+            self.pc += 1;
+            if let Err(exit) = ACTIONS_GROUP_DEFAULT[opcode as usize](self, host) {
+                return self.exit(exit, pc, opcode);
+            }
+        }
+    }
+
+    /// Run until halt or a sub-call, and call the inspector around each step.
+    pub(crate) fn run_inspected(&mut self, host: &mut Host, inspector: &mut dyn Inspector) -> Exit {
+        loop {
+            let pc = self.pc;
+            let opcode = self.code.get(pc).cloned().unwrap_or(0);
+            if let Err(halt) = inspector.step(self, host) {
+                return Exit::Halt(halt);
+            }
+            self.pc += 1;
+            let ret = ACTIONS_GROUP_INSPECTED[opcode as usize](self, host, inspector)
+                .map_err(|exit| self.exit(exit, pc, opcode));
+            let halt = match ret {
+                Err(Exit::Halt(halt)) => Some(halt),
+                _ => None,
+            };
+            inspector.step_end(self, host, halt);
+            if let Err(exit) = ret {
+                return exit;
+            }
+        }
+    }
+
+    /// Record where a stack error is raised, and halt the frame for it.
+    fn exit(&mut self, exit: Exit, pc: usize, opcode: u8) -> Exit {
+        match exit {
+            Exit::Stack(error) => {
+                self.stack_fault = Some(StackFault { pc, opcode, error });
+                Exit::Halt(error.into())
+            }
+            exit => exit,
+        }
+    }
+
+    /// Resume after a sub-call or a contract creation.
+    pub(crate) fn resume(
+        &mut self,
+        message: &Message,
+        output_range: (usize, usize),
+        result: ExecutionResult,
+    ) {
+        self.return_gas(result.gas_left);
+        let ret = if message.kind.is_create() {
+            // Only the output of a reverted creation is returned.
+            if result.halt.is_revert() {
+                self.return_data = result.output;
+            }
+            result.created_address.map_or(U256::ZERO, U256::from)
+        } else {
+            let (offset, size) = output_range;
+            let size = ::std::cmp::min(size, result.output.len());
+            self.memory.set(offset, &result.output[..size]);
+            let success = if result.halt.is_success() {
+                U256::ONE
+            } else {
+                U256::ZERO
+            };
+            self.return_data = result.output;
+            success
+        };
+        // The sub-call removed its arguments from the stack, so there is room for the result.
+        self.push(ret)
+            .expect("the stack should have room for the result of a sub-call");
+    }
+
+    #[inline]
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    #[inline]
+    pub fn code(&self) -> &[u8] {
+        &self.code[..]
+    }
+
+    #[inline]
+    pub fn gas(&self) -> u64 {
+        self.gas
+    }
+
+    #[inline]
+    pub fn stack(&self) -> &Stack {
+        &self.stack
+    }
+
+    #[inline]
+    pub fn stack_mut(&mut self) -> &mut Stack {
+        &mut self.stack
+    }
+
+    #[inline]
+    pub fn memory(&self) -> &Memory {
+        &self.memory
+    }
+
+    #[inline]
+    pub fn memory_mut(&mut self) -> &mut Memory {
+        &mut self.memory
+    }
+
+    /// The data returned by `RETURN` or `REVERT`.
+    #[inline]
+    pub fn output(&self) -> &[u8] {
+        &self.output[..]
+    }
+
+    /// The output of the last sub-call.
+    #[inline]
+    pub fn return_data(&self) -> &[u8] {
+        &self.return_data[..]
+    }
+
+    /// The depth of the call stack, zero for the outermost frame.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    #[inline]
+    pub fn is_static(&self) -> bool {
+        self.is_static
+    }
+
+    /// The stack error which halted the frame, with the instruction which raised it.
+    #[inline]
+    pub fn stack_fault(&self) -> Option<&StackFault> {
+        self.stack_fault.as_ref()
+    }
+
+    #[inline]
+    pub(crate) fn charge(&mut self, gas: u64) -> Result<(), Exit> {
+        if self.gas_free {
+            Ok(())
+        } else if self.gas < gas {
+            self.gas = 0;
+            Err(Halt::OutOfGas.into())
+        } else {
+            self.gas -= gas;
+            Ok(())
+        }
+    }
+
+    /// Give back the gas which was charged but not used, e.g. the gas left of a sub-call.
+    #[inline]
+    pub(crate) fn return_gas(&mut self, gas: u64) {
+        if !self.gas_free {
+            self.gas += gas;
+        }
+    }
+
+    #[inline]
+    pub(crate) fn pop(&mut self) -> Result<U256, Exit> {
+        Ok(U256::from_be_bytes(self.stack.pop()?))
+    }
+
+    #[inline]
+    pub(crate) fn push(&mut self, value: U256) -> Result<(), Exit> {
+        Ok(self.stack.push(&value.to_be_bytes()[..])?)
+    }
+
+    /// Fail if the frame is static.
+    #[inline]
+    pub(crate) fn check_static(&self) -> Result<(), Exit> {
+        if self.is_static {
+            Err(Halt::StaticModeViolation.into())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Whether `dest` is a valid destination for `JUMP` and `JUMPI`.
+    #[inline]
+    pub(crate) fn is_jumpdest(&self, dest: U256) -> bool {
+        dest.to_usize()
+            .and_then(|dest| self.jumpdests.get(dest).cloned())
+            .unwrap_or(false)
+    }
+
+    /// Expand the memory to access `[offset, offset + size)` and charge the gas for it.
+    ///
+    /// Return the range as `usize`, nothing is accessed if `size` is zero.
+    pub(crate) fn expand_memory(
+        &mut self,
+        offset: U256,
+        size: U256,
+    ) -> Result<(usize, usize), Exit> {
+        if size.is_zero() {
+            return Ok((0, 0));
+        }
+        let (offset, end) = match (offset.to_u64(), size.to_u64()) {
+            (Some(offset), Some(size)) => match offset.checked_add(size) {
+                Some(end) if end <= self.memory_limit => (offset, end),
+                _ => return Err(Halt::OutOfGas.into()),
+            },
+            _ => return Err(Halt::OutOfGas.into()),
+        };
+        let words = end.div_ceil(32);
+        let current = self.memory.words() as u64;
+        if words > current {
+            self.charge(schedule::memory_gas(words) - schedule::memory_gas(current))?;
+            self.memory.expand(end as usize);
+        }
+        Ok((offset as usize, (end - offset) as usize))
+    }
+}
+
+/// Find all `JUMPDEST`s which are not the immediate values of instructions.
+///
+/// The immediate values of `DUPN`, `SWAPN` and `EXCHANGE` are skipped only if they are
+/// available (EIP-663), otherwise they are invalid opcodes without immediate values.
+fn analyze_jumpdests(code: &[u8], eip663: bool) -> Vec<bool> {
+    let mut ret = vec![false; code.len()];
+    let mut pc = 0;
+    while pc < code.len() {
+        if code[pc] == OpKind::JUMPDEST.value() {
+            ret[pc] = true;
+        }
+        pc += 1 + opcodes::immediate_size(code[pc], eip663);
+    }
+    ret
+}
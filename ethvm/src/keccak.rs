@@ -0,0 +1,171 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Keccak-256, the hash function used by Ethereum.
+//!
+//! It is the original Keccak submitted to the SHA-3 competition, the padding is different from
+//! the standardized SHA3-256.
+
+use types::H256;
+
+/// The rate of Keccak-256 in bytes.
+const RATE: usize = 136;
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000_0000_0000_0001,
+    0x0000_0000_0000_8082,
+    0x8000_0000_0000_808a,
+    0x8000_0000_8000_8000,
+    0x0000_0000_0000_808b,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8009,
+    0x0000_0000_0000_008a,
+    0x0000_0000_0000_0088,
+    0x0000_0000_8000_8009,
+    0x0000_0000_8000_000a,
+    0x0000_0000_8000_808b,
+    0x8000_0000_0000_008b,
+    0x8000_0000_0000_8089,
+    0x8000_0000_0000_8003,
+    0x8000_0000_0000_8002,
+    0x8000_0000_0000_0080,
+    0x0000_0000_0000_800a,
+    0x8000_0000_8000_000a,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8080,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8008,
+];
+
+/// The rotation offsets of the `rho` step, in the order of the `pi` step.
+const RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+
+/// The lane indexes visited by the `pi` step.
+const PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+/// The hash of empty input.
+pub const KECCAK_EMPTY: H256 = H256([
+    0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7, 0x03, 0xc0,
+    0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04, 0x5d, 0x85, 0xa4, 0x70,
+]);
+
+/// The permutation Keccak-f[1600].
+fn keccak_f(state: &mut [u64; 25]) {
+    for rc in ROUND_CONSTANTS.iter() {
+        // theta
+        let mut c = [0u64; 5];
+        for (x, c) in c.iter_mut().enumerate() {
+            *c = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                state[x + 5 * y] ^= d;
+            }
+        }
+        // rho and pi
+        let mut last = state[1];
+        for (rho, pi) in RHO.iter().zip(PI.iter()) {
+            let tmp = state[*pi];
+            state[*pi] = last.rotate_left(*rho);
+            last = tmp;
+        }
+        // chi
+        for y in 0..5 {
+            let row = [
+                state[5 * y],
+                state[5 * y + 1],
+                state[5 * y + 2],
+                state[5 * y + 3],
+                state[5 * y + 4],
+            ];
+            for x in 0..5 {
+                state[x + 5 * y] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+        // iota
+        state[0] ^= rc;
+    }
+}
+
+/// An incremental Keccak-256 hasher.
+#[derive(Clone)]
+pub struct Keccak256 {
+    state: [u64; 25],
+    buffer: [u8; RATE],
+    buffered: usize,
+}
+
+impl ::std::default::Default for Keccak256 {
+    #[inline]
+    fn default() -> Self {
+        Keccak256 {
+            state: [0; 25],
+            buffer: [0; RATE],
+            buffered: 0,
+        }
+    }
+}
+
+impl Keccak256 {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Absorb more input.
+    pub fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let size = ::std::cmp::min(RATE - self.buffered, data.len());
+            self.buffer[self.buffered..self.buffered + size].copy_from_slice(&data[..size]);
+            self.buffered += size;
+            data = &data[size..];
+            if self.buffered == RATE {
+                self.absorb_buffer();
+            }
+        }
+    }
+
+    /// Pad the input and get the hash.
+    pub fn finalize(mut self) -> H256 {
+        for b in &mut self.buffer[self.buffered..] {
+            *b = 0;
+        }
+        self.buffer[self.buffered] |= 0x01;
+        self.buffer[RATE - 1] |= 0x80;
+        self.absorb_buffer();
+        let mut ret = [0u8; 32];
+        for (chunk, lane) in ret.chunks_mut(8).zip(self.state.iter()) {
+            chunk.copy_from_slice(&lane.to_le_bytes());
+        }
+        H256(ret)
+    }
+
+    fn absorb_buffer(&mut self) {
+        for (lane, chunk) in self.state.iter_mut().zip(self.buffer.chunks(8)) {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(chunk);
+            *lane ^= u64::from_le_bytes(bytes);
+        }
+        keccak_f(&mut self.state);
+        self.buffered = 0;
+    }
+}
+
+/// Compute the Keccak-256 hash of the input.
+#[inline]
+pub fn keccak256(data: &[u8]) -> H256 {
+    let mut hasher = Keccak256::new();
+    hasher.update(data);
+    hasher.finalize()
+}
@@ -6,11 +6,51 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#[cfg(feature = "kzg")]
+extern crate c_kzg;
 extern crate ethvm_internals;
+#[cfg(feature = "keccak")]
+extern crate k256;
+extern crate num_bigint;
+extern crate ripemd;
+extern crate serde;
+extern crate substrate_bn as bn;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+extern crate sha2;
 
 pub mod stack;
 
-mod opcodes; // Should be the first module.
-pub use opcodes::{OpCode, OpCodeStmt};
+mod opcodes;
+pub use opcodes::eof as eof_opcodes;
+pub use opcodes::error as opcode_error;
+pub use opcodes::{OpCode, OpCodeStmt, OpKind};
+
+pub mod instructions;
+pub use instructions::Instructions;
 
 pub mod actions;
+
+pub mod hex;
+#[cfg(feature = "keccak")]
+pub mod keccak;
+pub mod types;
+pub mod u256;
+pub use u256::U256;
+
+pub mod config;
+pub mod env;
+pub mod host;
+pub mod inspector;
+pub mod interpreter;
+pub mod log;
+pub mod memory;
+pub mod precompiles;
+pub mod rlp;
+pub mod schedule;
+pub mod state;
+pub mod tracer;
+pub mod transaction;
+#[cfg(feature = "keccak")]
+pub mod trie;
@@ -0,0 +1,128 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Events emitted by `LOG0` ... `LOG4`.
+
+use std::fmt;
+
+use hex;
+#[cfg(feature = "keccak")]
+use keccak;
+#[cfg(feature = "keccak")]
+use rlp::{self, Decodable, DecoderError, Encodable, Rlp};
+#[cfg(feature = "keccak")]
+use types::Bloom;
+use types::{Address, H256};
+
+/// A log record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Log {
+    pub address: Address,
+    pub topics: Vec<H256>,
+    #[serde(with = "hex::bytes")]
+    pub data: Vec<u8>,
+}
+
+impl fmt::Display for Log {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Log {{ address: {}, topics: [", self.address)?;
+        for (i, topic) in self.topics.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", topic)?;
+        }
+        write!(f, "], data: {} }}", hex::encode(&self.data))
+    }
+}
+
+#[cfg(feature = "keccak")]
+impl Bloom {
+    /// The bloom filter of logs, which contains the address and the topics of each log.
+    pub fn from_logs(logs: &[Log]) -> Self {
+        let mut bloom = Bloom::default();
+        for log in logs {
+            bloom.accrue_log(log);
+        }
+        bloom
+    }
+
+    /// Add the address and the topics of a log.
+    pub fn accrue_log(&mut self, log: &Log) {
+        self.accrue(log.address.as_bytes());
+        for topic in &log.topics {
+            self.accrue(topic.as_bytes());
+        }
+    }
+
+    /// Add an input, three bits chosen by its hash are set.
+    pub fn accrue(&mut self, input: &[u8]) {
+        for (index, bit) in Self::bits(input).iter() {
+            self.0[*index] |= bit;
+        }
+    }
+
+    /// Merge another bloom filter.
+    pub fn accrue_bloom(&mut self, other: &Bloom) {
+        for (byte, other) in self.0.iter_mut().zip(other.0.iter()) {
+            *byte |= other;
+        }
+    }
+
+    /// Check whether the input may have been added, false positives are possible.
+    pub fn contains_input(&self, input: &[u8]) -> bool {
+        Self::bits(input)
+            .iter()
+            .all(|(index, bit)| self.0[*index] & bit != 0)
+    }
+
+    /// The bytes and the bits in them for an input, from the low 11 bits of the first three
+    /// pairs of bytes of its hash.
+    fn bits(input: &[u8]) -> [(usize, u8); 3] {
+        let hash = keccak::keccak256(input);
+        let mut ret = [(0, 0); 3];
+        for (i, item) in ret.iter_mut().enumerate() {
+            let bit = (usize::from(hash.0[i * 2]) << 8 | usize::from(hash.0[i * 2 + 1])) & 0x7ff;
+            *item = (Bloom::SIZE - 1 - bit / 8, 1 << (bit % 8));
+        }
+        ret
+    }
+}
+
+#[cfg(feature = "keccak")]
+impl Encodable for Log {
+    /// The encoding in receipts: `[address, [topics...], data]`.
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        let mut payload = Vec::new();
+        self.address.rlp_append(&mut payload);
+        payload.extend_from_slice(&rlp::encode_list(&self.topics[..]));
+        self.data.rlp_append(&mut payload);
+        rlp::append_list(out, &payload);
+    }
+}
+
+#[cfg(feature = "keccak")]
+impl Decodable for Log {
+    fn rlp_decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        let items = rlp.items()?;
+        if items.len() != 3 {
+            return Err(DecoderError::BadSize);
+        }
+        Ok(Log {
+            address: items[0].as_val()?,
+            topics: items[1].as_list()?,
+            data: items[2].as_val()?,
+        })
+    }
+}
+
+/// The hash of the RLP encoding of the logs, which is used by the state tests.
+#[cfg(feature = "keccak")]
+pub fn logs_hash(logs: &[Log]) -> H256 {
+    keccak::keccak256(&rlp::encode_list(logs))
+}
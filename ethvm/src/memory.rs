@@ -0,0 +1,84 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/// EVM memory.
+///
+/// In EVM, the memory is a word-addressed byte array, it is expanded by words (32 bytes) and
+/// initialized to zeros.
+#[derive(Clone, Default)]
+pub struct Memory {
+    data: Vec<u8>,
+}
+
+impl ::std::fmt::Debug for Memory {
+    #[inline]
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        writeln!(f, "Memory {{ size = {} }}", self.data.len())
+    }
+}
+
+impl Memory {
+    /// The size in bytes, always a multiple of 32.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// The size in words.
+    #[inline]
+    pub fn words(&self) -> usize {
+        self.data.len() / 32
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.data[..]
+    }
+
+    /// Expand the memory to cover `[0, end)`, rounded up to a multiple of 32.
+    #[inline]
+    pub fn expand(&mut self, end: usize) {
+        let new_size = end.div_ceil(32) * 32;
+        if new_size > self.data.len() {
+            self.data.resize(new_size, 0);
+        }
+    }
+
+    /// Get a slice, the memory should have been expanded.
+    #[inline]
+    pub fn get(&self, offset: usize, size: usize) -> &[u8] {
+        &self.data[offset..offset + size]
+    }
+
+    /// Set a slice, the memory should have been expanded.
+    #[inline]
+    pub fn set(&mut self, offset: usize, value: &[u8]) {
+        self.data[offset..offset + value.len()].copy_from_slice(value);
+    }
+
+    #[inline]
+    pub fn set_byte(&mut self, offset: usize, value: u8) {
+        self.data[offset] = value;
+    }
+
+    /// Copy `size` bytes from `source[source_offset..]` into the memory, and pad the bytes out of
+    /// the range of `source` with zeros.
+    #[inline]
+    pub fn copy_padded(&mut self, offset: usize, source: &[u8], source_offset: usize, size: usize) {
+        let target = &mut self.data[offset..offset + size];
+        let available = if source_offset >= source.len() {
+            0
+        } else {
+            ::std::cmp::min(size, source.len() - source_offset)
+        };
+        target[..available].copy_from_slice(&source[source_offset..source_offset + available]);
+        for b in &mut target[available..] {
+            *b = 0;
+        }
+    }
+}
@@ -6,6 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::convert::TryFrom;
+
 use ethvm_internals;
 
 ethvm_internals::define_opcodes!([
@@ -48,6 +50,7 @@ ethvm_internals::define_opcodes!([
     (0x3c, EXTCODECOPY, [], 4, 0),
     (0x3d, RETURNDATASIZE, [], 0, 1),
     (0x3e, RETURNDATACOPY, [], 3, 0),
+    (0x3f, EXTCODEHASH, [], 1, 1),
     (0x40, BLOCKHASH, [], 1, 1),
     (0x41, COINBASE, [], 0, 1),
     (0x42, TIMESTAMP, [], 0, 1),
@@ -140,8 +143,38 @@ ethvm_internals::define_opcodes!([
     (0xf2, CALLCODE, [], 7, 1),
     (0xf3, RETURN, [], 2, 0),
     (0xf4, DELEGATECALL, [], 6, 1),
+    (0xf5, CREATE2, [], 4, 1),
     (0xfa, STATICCALL, [], 6, 1),
     (0xfd, REVERT, [], 2, 0),
     (0xfe, INVALID, [], 0, 0),
     (0xff, SELFDESTRUCT, [], 1, 0),
 ]);
+
+// The instructions of EIP-663, they are not in the legacy set, otherwise the decoders of legacy
+// code would take the bytes after them as their immediate values.
+ethvm_internals::define_opcodes!(pub mod eof, [
+    (0xe6, DUPN, [1], 1, 2),
+    (0xe7, SWAPN, [1], 2, 2),
+    (0xe8, EXCHANGE, [1], 3, 3),
+]);
+
+/// The mnemonic of an opcode, the instructions of EIP-663 are known only if they are enabled.
+pub(crate) fn mnemonic(opcode: u8, eip663: bool) -> Option<&'static str> {
+    match OpKind::try_from(opcode) {
+        Ok(kind) => Some(kind.mnemonic()),
+        Err(_) if eip663 => eof::OpKind::try_from(opcode)
+            .ok()
+            .map(eof::OpKind::mnemonic),
+        Err(_) => None,
+    }
+}
+
+/// The size of the immediate value of an opcode, the instructions of EIP-663 have immediate
+/// values only if they are enabled.
+pub(crate) fn immediate_size(opcode: u8, eip663: bool) -> usize {
+    match OpKind::try_from(opcode) {
+        Ok(kind) => kind.immediate_size(),
+        Err(_) if eip663 => eof::OpKind::try_from(opcode).map_or(0, eof::OpKind::immediate_size),
+        Err(_) => 0,
+    }
+}
@@ -0,0 +1,620 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Precompiled contracts, the native contracts at the addresses from 1.
+
+use std::cmp;
+use std::convert::TryFrom;
+
+use bn::{self, Group};
+#[cfg(feature = "kzg")]
+use c_kzg;
+#[cfg(feature = "keccak")]
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+#[cfg(feature = "keccak")]
+use keccak;
+use num_bigint::BigUint;
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+
+use interpreter::Halt;
+use schedule::Fork;
+use types::Address;
+use u256::U256;
+
+/// The output of a precompiled contract, or why it failed.
+pub type PrecompileResult = Result<Vec<u8>, Halt>;
+
+/// A precompiled contract.
+#[derive(Debug, Clone, Copy)]
+pub struct Precompile {
+    pub name: &'static str,
+    /// The gas cost for the input.
+    pub gas: fn(&[u8]) -> u64,
+    pub run: fn(&[u8]) -> PrecompileResult,
+}
+
+impl Precompile {
+    /// Run with the gas limit, return the output and the gas left.
+    ///
+    /// All gas is consumed if it fails.
+    pub fn execute(&self, input: &[u8], gas: u64) -> Result<(Vec<u8>, u64), Halt> {
+        let cost = (self.gas)(input);
+        if cost > gas {
+            return Err(Halt::OutOfGas);
+        }
+        let output = (self.run)(input)?;
+        Ok((output, gas - cost))
+    }
+}
+
+/// The precompiled contracts available in a fork.
+#[derive(Debug, Clone)]
+pub struct Precompiles {
+    /// The contract at the address `i + 1` is the `i`-th one.
+    contracts: Vec<Precompile>,
+}
+
+impl Precompiles {
+    pub fn new(fork: Fork) -> Self {
+        let mut contracts = vec![ECRECOVER, SHA256, RIPEMD160, IDENTITY];
+        if fork >= Fork::Byzantium {
+            // EIP-198, EIP-196 and EIP-197, repriced by EIP-2565 and EIP-1108.
+            if fork >= Fork::Berlin {
+                contracts.push(MODEXP_BERLIN);
+            } else {
+                contracts.push(MODEXP);
+            }
+            if fork >= Fork::Istanbul {
+                contracts.extend_from_slice(&[BN254_ADD_ISTANBUL, BN254_MUL_ISTANBUL]);
+                contracts.push(BN254_PAIRING_ISTANBUL);
+            } else {
+                contracts.extend_from_slice(&[BN254_ADD, BN254_MUL, BN254_PAIRING]);
+            }
+        }
+        if fork >= Fork::Istanbul {
+            // EIP-152
+            contracts.push(BLAKE2F);
+        }
+        if fork >= Fork::Cancun {
+            // EIP-4844
+            contracts.push(POINT_EVALUATION);
+        }
+        Precompiles { contracts }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.contracts.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.contracts.is_empty()
+    }
+
+    /// The precompiled contract at an address.
+    pub fn get(&self, address: &Address) -> Option<&Precompile> {
+        let index = U256::from(*address).to_usize()?;
+        if index == 0 {
+            return None;
+        }
+        self.contracts.get(index - 1)
+    }
+
+    /// The addresses of all precompiled contracts.
+    pub fn addresses(&self) -> impl Iterator<Item = Address> {
+        (1..=self.contracts.len() as u64).map(|i| U256::from(i).into())
+    }
+}
+
+const ECRECOVER: Precompile = Precompile {
+    name: "ecrecover",
+    gas: ecrecover_gas,
+    run: ecrecover,
+};
+
+const SHA256: Precompile = Precompile {
+    name: "sha256",
+    gas: sha256_gas,
+    run: sha256,
+};
+
+const RIPEMD160: Precompile = Precompile {
+    name: "ripemd160",
+    gas: ripemd160_gas,
+    run: ripemd160,
+};
+
+const IDENTITY: Precompile = Precompile {
+    name: "identity",
+    gas: identity_gas,
+    run: identity,
+};
+
+const MODEXP: Precompile = Precompile {
+    name: "modexp",
+    gas: modexp_gas,
+    run: modexp,
+};
+
+const MODEXP_BERLIN: Precompile = Precompile {
+    name: "modexp",
+    gas: modexp_gas_berlin,
+    run: modexp,
+};
+
+const BN254_ADD: Precompile = Precompile {
+    name: "bn254_add",
+    gas: bn254_add_gas,
+    run: bn254_add,
+};
+
+const BN254_ADD_ISTANBUL: Precompile = Precompile {
+    name: "bn254_add",
+    gas: bn254_add_gas_istanbul,
+    run: bn254_add,
+};
+
+const BN254_MUL: Precompile = Precompile {
+    name: "bn254_mul",
+    gas: bn254_mul_gas,
+    run: bn254_mul,
+};
+
+const BN254_MUL_ISTANBUL: Precompile = Precompile {
+    name: "bn254_mul",
+    gas: bn254_mul_gas_istanbul,
+    run: bn254_mul,
+};
+
+const BN254_PAIRING: Precompile = Precompile {
+    name: "bn254_pairing",
+    gas: bn254_pairing_gas,
+    run: bn254_pairing,
+};
+
+const BN254_PAIRING_ISTANBUL: Precompile = Precompile {
+    name: "bn254_pairing",
+    gas: bn254_pairing_gas_istanbul,
+    run: bn254_pairing,
+};
+
+const BLAKE2F: Precompile = Precompile {
+    name: "blake2f",
+    gas: blake2f_gas,
+    run: blake2f,
+};
+
+const POINT_EVALUATION: Precompile = Precompile {
+    name: "point_evaluation",
+    gas: point_evaluation_gas,
+    run: point_evaluation,
+};
+
+/// The number of words of the input.
+#[inline]
+fn words(input: &[u8]) -> u64 {
+    (input.len() as u64).div_ceil(32)
+}
+
+/// Take `size` bytes from `offset`, the bytes out of the input are zeros.
+fn padded(input: &[u8], offset: usize, size: usize) -> Vec<u8> {
+    let mut ret = vec![0u8; size];
+    if offset < input.len() {
+        let end = cmp::min(input.len(), offset.saturating_add(size));
+        ret[..end - offset].copy_from_slice(&input[offset..end]);
+    }
+    ret
+}
+
+/// Pad zeros to the left of the bytes.
+fn left_padded(bytes: &[u8], size: usize) -> Vec<u8> {
+    let mut ret = vec![0u8; size];
+    ret[size - bytes.len()..].copy_from_slice(bytes);
+    ret
+}
+
+fn ecrecover_gas(_input: &[u8]) -> u64 {
+    3000
+}
+
+/// Recover the address from a signature, the output is empty if the signature is invalid.
+#[cfg(feature = "keccak")]
+fn ecrecover(input: &[u8]) -> PrecompileResult {
+    let input = padded(input, 0, 128);
+    let v = input[63];
+    if input[32..63].iter().any(|b| *b != 0) || !(v == 27 || v == 28) {
+        return Ok(Vec::new());
+    }
+    let output = recover(&input[..32], &input[64..], v - 27)
+        .map_or_else(Vec::new, |address| left_padded(address.as_bytes(), 32));
+    Ok(output)
+}
+
+#[cfg(feature = "keccak")]
+fn recover(hash: &[u8], signature: &[u8], recovery_id: u8) -> Option<Address> {
+    let mut signature = Signature::from_slice(signature).ok()?;
+    let mut recovery_id = recovery_id;
+    // A signature with high `s` is valid here, it is equivalent to the one with low `s` and the
+    // other parity of `y`.
+    if let Some(normalized) = signature.normalize_s() {
+        signature = normalized;
+        recovery_id ^= 1;
+    }
+    let recovery_id = RecoveryId::from_byte(recovery_id)?;
+    let key = VerifyingKey::recover_from_prehash(hash, &signature, recovery_id).ok()?;
+    let point = key.to_encoded_point(false);
+    let hash = keccak::keccak256(&point.as_bytes()[1..]);
+    Some(Address::from_slice(&hash.as_bytes()[12..]))
+}
+
+#[cfg(not(feature = "keccak"))]
+fn ecrecover(_input: &[u8]) -> PrecompileResult {
+    Err(Halt::Internal)
+}
+
+fn sha256_gas(input: &[u8]) -> u64 {
+    60 + 12 * words(input)
+}
+
+fn sha256(input: &[u8]) -> PrecompileResult {
+    Ok(Sha256::digest(input).to_vec())
+}
+
+fn ripemd160_gas(input: &[u8]) -> u64 {
+    600 + 120 * words(input)
+}
+
+fn ripemd160(input: &[u8]) -> PrecompileResult {
+    Ok(left_padded(&Ripemd160::digest(input), 32))
+}
+
+fn identity_gas(input: &[u8]) -> u64 {
+    15 + 3 * words(input)
+}
+
+fn identity(input: &[u8]) -> PrecompileResult {
+    Ok(input.to_vec())
+}
+
+/// The lengths of the base, the exponent and the modulus of `modexp`.
+fn modexp_lengths(input: &[u8]) -> (U256, U256, U256) {
+    let header = padded(input, 0, 96);
+    (
+        U256::from_be_slice(&header[..32]),
+        U256::from_be_slice(&header[32..64]),
+        U256::from_be_slice(&header[64..]),
+    )
+}
+
+/// The larger length of the base and the modulus, and the adjusted length of the exponent, which
+/// is about the bit length of the exponent divided by 8.
+fn modexp_complexity_params(input: &[u8]) -> (u128, u128) {
+    let (base_len, exp_len, mod_len) = modexp_lengths(input);
+    let saturated = |v: U256| v.to_u64().unwrap_or(u64::MAX);
+    let (base_len, exp_len, mod_len) =
+        (saturated(base_len), saturated(exp_len), saturated(mod_len));
+    let data_len = (input.len() as u64).saturating_sub(96);
+    let exp_head = if data_len <= base_len {
+        U256::ZERO
+    } else {
+        let size = cmp::min(exp_len, 32) as usize;
+        U256::from_be_slice(&padded(input, 96 + base_len as usize, size))
+    };
+    let msb = exp_head.bits().saturating_sub(1) as u64;
+    let adjusted_exp_len = exp_len
+        .saturating_sub(32)
+        .saturating_mul(8)
+        .saturating_add(msb);
+    (
+        u128::from(cmp::max(base_len, mod_len)),
+        u128::from(cmp::max(adjusted_exp_len, 1)),
+    )
+}
+
+/// The gas cost of `modexp` (EIP-198).
+fn modexp_gas(input: &[u8]) -> u64 {
+    let (len, exp) = modexp_complexity_params(input);
+    let complexity = if len <= 64 {
+        Some(len * len)
+    } else if len <= 1024 {
+        (len * len / 4 + 96 * len).checked_sub(3072)
+    } else {
+        len.checked_mul(len)
+            .map(|square| square / 16 + 480 * len - 199_680)
+    };
+    complexity
+        .and_then(|complexity| complexity.checked_mul(exp))
+        .and_then(|gas| u64::try_from(gas / 20).ok())
+        .unwrap_or(u64::MAX)
+}
+
+/// The gas cost of `modexp` since Berlin (EIP-2565).
+fn modexp_gas_berlin(input: &[u8]) -> u64 {
+    let (len, exp) = modexp_complexity_params(input);
+    let words = len.div_ceil(8);
+    let gas = words
+        .checked_mul(words)
+        .and_then(|complexity| complexity.checked_mul(exp))
+        .and_then(|gas| u64::try_from(gas / 3).ok())
+        .unwrap_or(u64::MAX);
+    cmp::max(gas, 200)
+}
+
+/// The maximum of each length of `modexp`, the gas cost of the length is far beyond the gas limit
+/// of any block, so it only limits the inputs when the gas is free.
+const MODEXP_MAX_LENGTH: usize = 1 << 20;
+
+fn modexp(input: &[u8]) -> PrecompileResult {
+    let (base_len, exp_len, mod_len) = modexp_lengths(input);
+    if base_len.is_zero() && mod_len.is_zero() {
+        return Ok(Vec::new());
+    }
+    // The lengths are limited by the gas.
+    let length = |v: U256| {
+        v.to_usize()
+            .filter(|&len| len <= MODEXP_MAX_LENGTH)
+            .ok_or(Halt::OutOfGas)
+    };
+    let (base_len, exp_len, mod_len) = (length(base_len)?, length(exp_len)?, length(mod_len)?);
+    let offset = |start: usize, len: usize| start.checked_add(len).ok_or(Halt::OutOfGas);
+    let exp_offset = offset(96, base_len)?;
+    let mod_offset = offset(exp_offset, exp_len)?;
+    let base = BigUint::from_bytes_be(&padded(input, 96, base_len));
+    let exp = BigUint::from_bytes_be(&padded(input, exp_offset, exp_len));
+    let modulus = BigUint::from_bytes_be(&padded(input, mod_offset, mod_len));
+    if modulus == BigUint::default() {
+        return Ok(vec![0u8; mod_len]);
+    }
+    let result = base.modpow(&exp, &modulus);
+    Ok(left_padded(&result.to_bytes_be(), mod_len))
+}
+
+fn bn254_add_gas(_input: &[u8]) -> u64 {
+    500
+}
+
+fn bn254_add_gas_istanbul(_input: &[u8]) -> u64 {
+    150
+}
+
+fn bn254_mul_gas(_input: &[u8]) -> u64 {
+    40000
+}
+
+fn bn254_mul_gas_istanbul(_input: &[u8]) -> u64 {
+    6000
+}
+
+fn bn254_pairing_gas(input: &[u8]) -> u64 {
+    100_000 + 80000 * (input.len() / 192) as u64
+}
+
+fn bn254_pairing_gas_istanbul(input: &[u8]) -> u64 {
+    45000 + 34000 * (input.len() / 192) as u64
+}
+
+fn read_fq(bytes: &[u8]) -> Result<bn::Fq, Halt> {
+    bn::Fq::from_slice(bytes).map_err(|_| Halt::PrecompileFailed)
+}
+
+/// Read a point of G1 from 64 bytes, the point at infinity is `(0, 0)`.
+fn read_g1(bytes: &[u8]) -> Result<bn::G1, Halt> {
+    let x = read_fq(&bytes[..32])?;
+    let y = read_fq(&bytes[32..64])?;
+    if x == bn::Fq::zero() && y == bn::Fq::zero() {
+        return Ok(bn::G1::zero());
+    }
+    bn::AffineG1::new(x, y)
+        .map(Into::into)
+        .map_err(|_| Halt::PrecompileFailed)
+}
+
+/// Read a point of G2 from 128 bytes, the imaginary part of each coordinate comes first.
+fn read_g2(bytes: &[u8]) -> Result<bn::G2, Halt> {
+    let x = bn::Fq2::new(read_fq(&bytes[32..64])?, read_fq(&bytes[..32])?);
+    let y = bn::Fq2::new(read_fq(&bytes[96..128])?, read_fq(&bytes[64..96])?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(bn::G2::zero());
+    }
+    bn::AffineG2::new(x, y)
+        .map(Into::into)
+        .map_err(|_| Halt::PrecompileFailed)
+}
+
+fn write_g1(point: bn::G1) -> Vec<u8> {
+    let mut ret = vec![0u8; 64];
+    if let Some(point) = bn::AffineG1::from_jacobian(point) {
+        point
+            .x()
+            .to_big_endian(&mut ret[..32])
+            .expect("the slice is 32 bytes");
+        point
+            .y()
+            .to_big_endian(&mut ret[32..])
+            .expect("the slice is 32 bytes");
+    }
+    ret
+}
+
+fn bn254_add(input: &[u8]) -> PrecompileResult {
+    let input = padded(input, 0, 128);
+    let a = read_g1(&input[..64])?;
+    let b = read_g1(&input[64..])?;
+    Ok(write_g1(a + b))
+}
+
+fn bn254_mul(input: &[u8]) -> PrecompileResult {
+    let input = padded(input, 0, 96);
+    let point = read_g1(&input[..64])?;
+    let scalar = bn::Fr::from_slice(&input[64..]).map_err(|_| Halt::PrecompileFailed)?;
+    Ok(write_g1(point * scalar))
+}
+
+fn bn254_pairing(input: &[u8]) -> PrecompileResult {
+    if !input.len().is_multiple_of(192) {
+        return Err(Halt::PrecompileFailed);
+    }
+    let mut pairs = Vec::with_capacity(input.len() / 192);
+    for chunk in input.chunks(192) {
+        pairs.push((read_g1(&chunk[..64])?, read_g2(&chunk[64..])?));
+    }
+    let success = pairs.is_empty() || bn::pairing_batch(&pairs) == bn::Gt::one();
+    let ret = if success { U256::ONE } else { U256::ZERO };
+    Ok(ret.to_be_bytes().to_vec())
+}
+
+const BLAKE2B_IV: [u64; 8] = [
+    0x6a09_e667_f3bc_c908,
+    0xbb67_ae85_84ca_a73b,
+    0x3c6e_f372_fe94_f82b,
+    0xa54f_f53a_5f1d_36f1,
+    0x510e_527f_ade6_82d1,
+    0x9b05_688c_2b3e_6c1f,
+    0x1f83_d9ab_fb41_bd6b,
+    0x5be0_cd19_137e_2179,
+];
+
+const BLAKE2B_SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// The size of the input of `blake2f`: rounds, state, message, offset counters and final flag.
+const BLAKE2F_INPUT_SIZE: usize = 4 + 64 + 128 + 16 + 1;
+
+fn blake2f_gas(input: &[u8]) -> u64 {
+    if input.len() != BLAKE2F_INPUT_SIZE {
+        return 0;
+    }
+    u64::from(u32::from_be_bytes([input[0], input[1], input[2], input[3]]))
+}
+
+#[inline]
+fn blake2b_mix(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+/// The compression function `F` of BLAKE2b, with the number of rounds.
+fn blake2b_compress(rounds: u32, h: &mut [u64; 8], m: &[u64; 16], t: [u64; 2], last: bool) {
+    let mut v = [0u64; 16];
+    v[..8].copy_from_slice(&h[..]);
+    v[8..].copy_from_slice(&BLAKE2B_IV[..]);
+    v[12] ^= t[0];
+    v[13] ^= t[1];
+    if last {
+        v[14] = !v[14];
+    }
+    for i in 0..rounds as usize {
+        let s = &BLAKE2B_SIGMA[i % 10];
+        blake2b_mix(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+        blake2b_mix(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+        blake2b_mix(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+        blake2b_mix(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+        blake2b_mix(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+        blake2b_mix(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+        blake2b_mix(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+        blake2b_mix(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+    }
+    for i in 0..8 {
+        h[i] ^= v[i] ^ v[i + 8];
+    }
+}
+
+fn blake2f(input: &[u8]) -> PrecompileResult {
+    if input.len() != BLAKE2F_INPUT_SIZE {
+        return Err(Halt::PrecompileFailed);
+    }
+    let word = |offset: usize| {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&input[offset..offset + 8]);
+        u64::from_le_bytes(bytes)
+    };
+    let rounds = u32::from_be_bytes([input[0], input[1], input[2], input[3]]);
+    let mut h = [0u64; 8];
+    for (i, v) in h.iter_mut().enumerate() {
+        *v = word(4 + i * 8);
+    }
+    let mut m = [0u64; 16];
+    for (i, v) in m.iter_mut().enumerate() {
+        *v = word(68 + i * 8);
+    }
+    let t = [word(196), word(204)];
+    let last = match input[212] {
+        0 => false,
+        1 => true,
+        _ => return Err(Halt::PrecompileFailed),
+    };
+    blake2b_compress(rounds, &mut h, &m, t, last);
+    let mut output = Vec::with_capacity(64);
+    for v in &h {
+        output.extend_from_slice(&v.to_le_bytes());
+    }
+    Ok(output)
+}
+
+fn point_evaluation_gas(_input: &[u8]) -> u64 {
+    50000
+}
+
+/// The number of field elements in a blob, and the modulus of the scalar field of BLS12-381.
+#[cfg(feature = "kzg")]
+const POINT_EVALUATION_OUTPUT: [u8; 64] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00,
+    0x73, 0xed, 0xa7, 0x53, 0x29, 0x9d, 0x7d, 0x48, 0x33, 0x39, 0xd8, 0x08, 0x09, 0xa1, 0xd8, 0x05,
+    0x53, 0xbd, 0xa4, 0x02, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01,
+];
+
+/// Verify that the blob of a versioned hash evaluates to `y` at `z`.
+#[cfg(feature = "kzg")]
+fn point_evaluation(input: &[u8]) -> PrecompileResult {
+    if input.len() != 192 {
+        return Err(Halt::PrecompileFailed);
+    }
+    let commitment = &input[96..144];
+    let mut versioned_hash = Sha256::digest(commitment);
+    // The version of KZG commitments.
+    versioned_hash[0] = 0x01;
+    if versioned_hash[..] != input[..32] {
+        return Err(Halt::PrecompileFailed);
+    }
+    let bytes48 = |bytes: &[u8]| c_kzg::Bytes48::from_bytes(bytes).expect("it is 48 bytes");
+    let bytes32 = |bytes: &[u8]| c_kzg::Bytes32::from_bytes(bytes).expect("it is 32 bytes");
+    let verified = c_kzg::ethereum_kzg_settings(0).verify_kzg_proof(
+        &bytes48(commitment),
+        &bytes32(&input[32..64]),
+        &bytes32(&input[64..96]),
+        &bytes48(&input[144..]),
+    );
+    match verified {
+        Ok(true) => Ok(POINT_EVALUATION_OUTPUT.to_vec()),
+        _ => Err(Halt::PrecompileFailed),
+    }
+}
+
+#[cfg(not(feature = "kzg"))]
+fn point_evaluation(_input: &[u8]) -> PrecompileResult {
+    Err(Halt::Internal)
+}
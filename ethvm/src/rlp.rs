@@ -0,0 +1,322 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Recursive Length Prefix, the serialization used by Ethereum.
+//!
+//! An item is a byte string or a list of items. Integers are byte strings in big-endian without
+//! leading zeros.
+//!
+//! The decoder is strict, only the canonical encoding of an item is accepted.
+
+use std::convert::TryFrom;
+
+use types::{Address, H256};
+use u256::U256;
+
+/// The offset of the first byte of a byte string.
+const OFFSET_STRING: u8 = 0x80;
+/// The offset of the first byte of a list.
+const OFFSET_LIST: u8 = 0xc0;
+
+/// Errors when decode an item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecoderError {
+    /// The input ends in the middle of an item.
+    TooShort,
+    /// There are bytes after the item.
+    TrailingBytes,
+    /// The item is not in its shortest encoding.
+    NonCanonical,
+    ExpectedBytes,
+    ExpectedList,
+    /// The size of the byte string or the number of the items does not fit the type.
+    BadSize,
+}
+
+/// Types which could be encoded as an item.
+pub trait Encodable {
+    /// Append the encoding to the output.
+    fn rlp_append(&self, out: &mut Vec<u8>);
+}
+
+/// Types which could be decoded from an item.
+pub trait Decodable: Sized {
+    fn rlp_decode(rlp: &Rlp) -> Result<Self, DecoderError>;
+}
+
+/// Encode a value.
+#[inline]
+pub fn encode<T: Encodable + ?Sized>(value: &T) -> Vec<u8> {
+    let mut out = Vec::new();
+    value.rlp_append(&mut out);
+    out
+}
+
+/// Encode a list whose items have the same type.
+pub fn encode_list<T: Encodable>(items: &[T]) -> Vec<u8> {
+    let mut payload = Vec::new();
+    for item in items {
+        item.rlp_append(&mut payload);
+    }
+    let mut out = Vec::with_capacity(payload.len() + 9);
+    append_list(&mut out, &payload);
+    out
+}
+
+/// Decode a value, the input should be exactly one item.
+#[inline]
+pub fn decode<T: Decodable>(bytes: &[u8]) -> Result<T, DecoderError> {
+    T::rlp_decode(&Rlp::new(bytes)?)
+}
+
+/// Decode a list whose items have the same type.
+#[inline]
+pub fn decode_list<T: Decodable>(bytes: &[u8]) -> Result<Vec<T>, DecoderError> {
+    Rlp::new(bytes)?.as_list()
+}
+
+/// Append a list, the payload is the concatenation of the encoded items.
+pub fn append_list(out: &mut Vec<u8>, payload: &[u8]) {
+    append_header(out, OFFSET_LIST, payload.len());
+    out.extend_from_slice(payload);
+}
+
+/// Append a byte string.
+pub fn append_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    if bytes.len() == 1 && bytes[0] < OFFSET_STRING {
+        out.push(bytes[0]);
+    } else {
+        append_header(out, OFFSET_STRING, bytes.len());
+        out.extend_from_slice(bytes);
+    }
+}
+
+/// Append the header of a byte string or a list, the length is in the header if it is less
+/// than 56, otherwise the length of the length is.
+fn append_header(out: &mut Vec<u8>, offset: u8, len: usize) {
+    if len < 56 {
+        out.push(offset + len as u8);
+    } else {
+        let len_bytes = (len as u64).to_be_bytes();
+        let len_bytes = &len_bytes[(len.leading_zeros() / 8) as usize..];
+        out.push(offset + 55 + len_bytes.len() as u8);
+        out.extend_from_slice(len_bytes);
+    }
+}
+
+/// An undecoded item, which is a view of the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rlp<'a> {
+    is_list: bool,
+    payload: &'a [u8],
+}
+
+impl<'a> Rlp<'a> {
+    /// Parse an item, the input should be exactly one item.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, DecoderError> {
+        let (item, rest) = Rlp::parse(bytes)?;
+        if !rest.is_empty() {
+            return Err(DecoderError::TrailingBytes);
+        }
+        Ok(item)
+    }
+
+    /// Parse the first item of the input, return it and the rest of the input.
+    pub fn parse(bytes: &'a [u8]) -> Result<(Self, &'a [u8]), DecoderError> {
+        let first = *bytes.first().ok_or(DecoderError::TooShort)?;
+        if first < OFFSET_STRING {
+            let item = Rlp {
+                is_list: false,
+                payload: &bytes[..1],
+            };
+            return Ok((item, &bytes[1..]));
+        }
+        let (is_list, offset) = if first < OFFSET_LIST {
+            (false, OFFSET_STRING)
+        } else {
+            (true, OFFSET_LIST)
+        };
+        let (header_len, len) = if first - offset < 56 {
+            (1, usize::from(first - offset))
+        } else {
+            let len_len = usize::from(first - offset - 55);
+            let len_bytes = bytes.get(1..=len_len).ok_or(DecoderError::TooShort)?;
+            if len_bytes[0] == 0 {
+                return Err(DecoderError::NonCanonical);
+            }
+            if len_len > 8 {
+                return Err(DecoderError::TooShort);
+            }
+            let len = len_bytes
+                .iter()
+                .fold(0u64, |len, b| (len << 8) | u64::from(*b));
+            if len < 56 {
+                return Err(DecoderError::NonCanonical);
+            }
+            let len = usize::try_from(len).map_err(|_| DecoderError::TooShort)?;
+            (1 + len_len, len)
+        };
+        let end = header_len
+            .checked_add(len)
+            .filter(|end| *end <= bytes.len())
+            .ok_or(DecoderError::TooShort)?;
+        let payload = &bytes[header_len..end];
+        // A single byte less than `0x80` is encoded as itself.
+        if !is_list && len == 1 && payload[0] < OFFSET_STRING {
+            return Err(DecoderError::NonCanonical);
+        }
+        Ok((Rlp { is_list, payload }, &bytes[end..]))
+    }
+
+    #[inline]
+    pub fn is_list(&self) -> bool {
+        self.is_list
+    }
+
+    /// The content of a byte string.
+    #[inline]
+    pub fn data(&self) -> Result<&'a [u8], DecoderError> {
+        if self.is_list {
+            Err(DecoderError::ExpectedBytes)
+        } else {
+            Ok(self.payload)
+        }
+    }
+
+    /// The items of a list.
+    pub fn items(&self) -> Result<Vec<Rlp<'a>>, DecoderError> {
+        if !self.is_list {
+            return Err(DecoderError::ExpectedList);
+        }
+        let mut items = Vec::new();
+        let mut rest = self.payload;
+        while !rest.is_empty() {
+            let (item, next) = Rlp::parse(rest)?;
+            items.push(item);
+            rest = next;
+        }
+        Ok(items)
+    }
+
+    #[inline]
+    pub fn as_val<T: Decodable>(&self) -> Result<T, DecoderError> {
+        T::rlp_decode(self)
+    }
+
+    /// Decode a list whose items have the same type.
+    pub fn as_list<T: Decodable>(&self) -> Result<Vec<T>, DecoderError> {
+        self.items()?.iter().map(Rlp::as_val).collect()
+    }
+}
+
+/// The content of an integer, which has no leading zeros and is not longer than `size`.
+fn integer_bytes<'a>(rlp: &Rlp<'a>, size: usize) -> Result<&'a [u8], DecoderError> {
+    let bytes = rlp.data()?;
+    if bytes.first() == Some(&0) {
+        Err(DecoderError::NonCanonical)
+    } else if bytes.len() > size {
+        Err(DecoderError::BadSize)
+    } else {
+        Ok(bytes)
+    }
+}
+
+impl Encodable for [u8] {
+    #[inline]
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        append_bytes(out, self);
+    }
+}
+
+impl Encodable for Vec<u8> {
+    #[inline]
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        append_bytes(out, &self[..]);
+    }
+}
+
+impl Encodable for u64 {
+    #[inline]
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        let bytes = self.to_be_bytes();
+        append_bytes(out, &bytes[(self.leading_zeros() / 8) as usize..]);
+    }
+}
+
+impl Encodable for U256 {
+    #[inline]
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        let bytes = self.to_be_bytes();
+        append_bytes(out, &bytes[self.leading_zeros() / 8..]);
+    }
+}
+
+impl Encodable for Address {
+    #[inline]
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        append_bytes(out, self.as_bytes());
+    }
+}
+
+impl Encodable for H256 {
+    #[inline]
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        append_bytes(out, self.as_bytes());
+    }
+}
+
+impl<T: Encodable + ?Sized> Encodable for &T {
+    #[inline]
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        (**self).rlp_append(out);
+    }
+}
+
+impl Decodable for Vec<u8> {
+    #[inline]
+    fn rlp_decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        rlp.data().map(<[u8]>::to_vec)
+    }
+}
+
+impl Decodable for u64 {
+    #[inline]
+    fn rlp_decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        let bytes = integer_bytes(rlp, 8)?;
+        Ok(bytes.iter().fold(0, |v, b| (v << 8) | u64::from(*b)))
+    }
+}
+
+impl Decodable for U256 {
+    #[inline]
+    fn rlp_decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        integer_bytes(rlp, 32).map(U256::from_be_slice)
+    }
+}
+
+impl Decodable for Address {
+    #[inline]
+    fn rlp_decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        let bytes = rlp.data()?;
+        if bytes.len() != Address::SIZE {
+            return Err(DecoderError::BadSize);
+        }
+        Ok(Address::from_slice(bytes))
+    }
+}
+
+impl Decodable for H256 {
+    #[inline]
+    fn rlp_decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        let bytes = rlp.data()?;
+        if bytes.len() != H256::SIZE {
+            return Err(DecoderError::BadSize);
+        }
+        Ok(H256::from_slice(bytes))
+    }
+}
@@ -0,0 +1,318 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Hard forks and the gas schedules of them.
+
+use std::{fmt, str};
+
+/// The gas cost of the instructions in the set `W_base`.
+pub const GAS_BASE: u64 = 2;
+/// The gas cost of the instructions in the set `W_verylow`.
+pub const GAS_VERYLOW: u64 = 3;
+/// The gas cost of the instructions in the set `W_low`.
+pub const GAS_LOW: u64 = 5;
+/// The gas cost of the instructions in the set `W_mid`.
+pub const GAS_MID: u64 = 8;
+/// The gas cost of the instructions in the set `W_high`.
+pub const GAS_HIGH: u64 = 10;
+/// The gas cost of `JUMPDEST`.
+pub const GAS_JUMPDEST: u64 = 1;
+/// The gas cost of `EXP`, without the cost of the exponent.
+pub const GAS_EXP: u64 = 10;
+/// The linear coefficient of the memory cost for each word.
+pub const GAS_MEMORY: u64 = 3;
+/// The divisor of the quadratic part of the memory cost.
+pub const GAS_QUAD_COEFF_DIV: u64 = 512;
+/// The gas cost for each word copied by `*COPY` instructions.
+pub const GAS_COPY: u64 = 3;
+/// The gas cost of `BLOCKHASH`.
+pub const GAS_BLOCKHASH: u64 = 20;
+/// The gas cost of `SHA3`, without the cost of the data.
+pub const GAS_SHA3: u64 = 30;
+/// The gas cost for each word of the data of `SHA3`.
+pub const GAS_SHA3_WORD: u64 = 6;
+/// The gas cost of `LOG*`, without the cost of topics and data.
+pub const GAS_LOG: u64 = 375;
+/// The gas cost for each topic of `LOG*`.
+pub const GAS_LOG_TOPIC: u64 = 375;
+/// The gas cost for each byte of the data of `LOG*`.
+pub const GAS_LOG_DATA: u64 = 8;
+/// The additional gas cost of `CALL` and `CALLCODE` when the value is not zero.
+pub const GAS_CALL_VALUE: u64 = 9000;
+/// The free gas given to the callee when the value is not zero.
+pub const GAS_CALL_STIPEND: u64 = 2300;
+/// The additional gas cost of `CALL` when it creates a new account.
+pub const GAS_NEW_ACCOUNT: u64 = 25000;
+/// The gas cost of `CREATE` and `CREATE2`, without the cost of the init code.
+pub const GAS_CREATE: u64 = 32000;
+/// The gas cost for each byte of the code of a new contract.
+pub const GAS_CODE_DEPOSIT: u64 = 200;
+/// The gas cost for each word of the init code (EIP-3860).
+pub const GAS_INITCODE_WORD: u64 = 2;
+/// The gas cost to read a storage slot which is accessed before (EIP-2929).
+pub const GAS_WARM_STORAGE_READ: u64 = 100;
+/// The gas cost to read a storage slot which is not accessed before (EIP-2929).
+pub const GAS_COLD_SLOAD: u64 = 2100;
+/// The gas cost to access an account which is not accessed before (EIP-2929).
+pub const GAS_COLD_ACCOUNT_ACCESS: u64 = 2600;
+/// The gas charged for every transaction.
+pub const GAS_TX: u64 = 21000;
+/// The gas cost for each zero byte of the data of a transaction.
+pub const GAS_TX_DATA_ZERO: u64 = 4;
+/// The gas cost for each address in the access list of a transaction (EIP-2930).
+pub const GAS_TX_ACCESS_LIST_ADDRESS: u64 = 2400;
+/// The gas cost for each storage key in the access list of a transaction (EIP-2930).
+pub const GAS_TX_ACCESS_LIST_STORAGE_KEY: u64 = 1900;
+/// The maximum size of the code of a contract (EIP-170).
+pub const MAX_CODE_SIZE: usize = 0x6000;
+/// The maximum size of the init code (EIP-3860).
+pub const MAX_INITCODE_SIZE: usize = 2 * MAX_CODE_SIZE;
+
+/// The total memory cost for `words` words, it saturates at `u64::MAX` for a huge memory, which
+/// is only reachable with a large `memory_limit` of `VmConfig`.
+#[inline]
+pub fn memory_gas(words: u64) -> u64 {
+    (GAS_MEMORY * words).saturating_add(words.saturating_mul(words) / GAS_QUAD_COEFF_DIV)
+}
+
+/// The gas cost to copy `size` bytes.
+#[inline]
+pub fn copy_gas(size: u64) -> u64 {
+    GAS_COPY * size.div_ceil(32)
+}
+
+/// Hard forks of the Ethereum main network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Fork {
+    Frontier,
+    Homestead,
+    TangerineWhistle,
+    SpuriousDragon,
+    Byzantium,
+    Constantinople,
+    Petersburg,
+    Istanbul,
+    Berlin,
+    London,
+    Merge,
+    Shanghai,
+    Cancun,
+}
+
+impl Fork {
+    /// The latest fork whose instructions are all implemented, the instructions introduced
+    /// since Constantinople (e.g. `SHL`, `CHAINID`, `PUSH0`) are not.
+    pub const LATEST_SUPPORTED: Fork = Fork::Byzantium;
+
+    /// All instructions of the fork are implemented, the rules of the later forks are still
+    /// available for the gas costs and the transactions.
+    #[inline]
+    pub fn is_supported(self) -> bool {
+        self <= Fork::LATEST_SUPPORTED
+    }
+}
+
+/// Errors when parse `Fork` from a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownFork(pub String);
+
+impl fmt::Display for Fork {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl str::FromStr for Fork {
+    type Err = UnknownFork;
+    /// Parse from the name of a fork, the aliases used in the Ethereum tests are accepted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fork = match s {
+            "Frontier" => Fork::Frontier,
+            "Homestead" => Fork::Homestead,
+            "TangerineWhistle" | "EIP150" => Fork::TangerineWhistle,
+            "SpuriousDragon" | "EIP158" => Fork::SpuriousDragon,
+            "Byzantium" => Fork::Byzantium,
+            "Constantinople" => Fork::Constantinople,
+            "Petersburg" | "ConstantinopleFix" => Fork::Petersburg,
+            "Istanbul" => Fork::Istanbul,
+            "Berlin" => Fork::Berlin,
+            "London" => Fork::London,
+            "Merge" | "Paris" => Fork::Merge,
+            "Shanghai" => Fork::Shanghai,
+            "Cancun" => Fork::Cancun,
+            _ => return Err(UnknownFork(s.to_owned())),
+        };
+        Ok(fork)
+    }
+}
+
+/// The rules and the gas costs which are changed by hard forks.
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    pub fork: Fork,
+    /// `REVERT` is available (EIP-140).
+    pub have_revert: bool,
+    /// `DELEGATECALL` is available (EIP-7).
+    pub have_delegatecall: bool,
+    /// `STATICCALL` is available (EIP-214).
+    pub have_staticcall: bool,
+    /// `RETURNDATASIZE` and `RETURNDATACOPY` are available (EIP-211).
+    pub have_return_data: bool,
+    /// `EXTCODEHASH` is available (EIP-1052).
+    pub have_extcodehash: bool,
+    /// `CREATE2` is available (EIP-1014).
+    pub have_create2: bool,
+    /// The gas cost for each byte of the exponent of `EXP`.
+    pub exp_byte_gas: u64,
+    /// The gas cost of `SLOAD`, it is the cost of a warm slot since Berlin.
+    pub sload_gas: u64,
+    /// The gas cost of `BALANCE`, it is the cost of a warm account since Berlin, the same for
+    /// `EXTCODE*` and the call instructions.
+    pub balance_gas: u64,
+    /// The gas cost of `EXTCODESIZE` and `EXTCODECOPY`.
+    pub extcode_gas: u64,
+    pub extcodehash_gas: u64,
+    /// The gas cost of the call instructions, without the cost of the value and the new account.
+    pub call_gas: u64,
+    /// At most all but one 64th of the gas left is forwarded to the sub-call (EIP-150).
+    pub all_but_one_64th: bool,
+    /// The gas cost of `SSTORE` when set a zero slot to non-zero.
+    pub sstore_set_gas: u64,
+    /// The gas cost of `SSTORE` in other cases, without the cost of the cold slot.
+    pub sstore_reset_gas: u64,
+    /// The refund of `SSTORE` when clear a non-zero slot.
+    pub sstore_clears_refund: u64,
+    /// The gas cost of `SSTORE` depends on the original value of the slot, and a slot which is
+    /// changed already costs `sload_gas` (EIP-1283, EIP-2200).
+    pub net_sstore_metering: bool,
+    /// `SSTORE` fails if the gas left is not more than the call stipend (EIP-2200).
+    pub sstore_sentry: bool,
+    pub selfdestruct_gas: u64,
+    /// The additional gas cost of `SELFDESTRUCT` when it creates a new account.
+    pub selfdestruct_new_account_gas: u64,
+    pub selfdestruct_refund: u64,
+    /// Touched empty accounts are treated as nonexistent, and new contracts start with nonce
+    /// one (EIP-161).
+    pub no_empty: bool,
+    /// Running out of gas for the code deposit fails the creation, instead of leaving the
+    /// contract without code (EIP-2).
+    pub fail_on_code_deposit: bool,
+    /// The size of the code of new contracts is limited (EIP-170).
+    pub limit_code_size: bool,
+    /// The code of new contracts must not start with `0xef` (EIP-3541).
+    pub reject_ef_code: bool,
+    /// The size of the init code is limited and charged for each word (EIP-3860).
+    pub limit_initcode_size: bool,
+    /// The gas charged for a transaction which creates a contract.
+    pub tx_create_gas: u64,
+    /// The gas cost for each non-zero byte of the data of a transaction.
+    pub tx_data_non_zero_gas: u64,
+    /// The accessed addresses and storage slots are tracked, the first access costs more
+    /// (EIP-2929), and transactions could have access lists (EIP-2930).
+    pub have_access_list: bool,
+    /// The coinbase is accessed at the start of a transaction (EIP-3651).
+    pub warm_coinbase: bool,
+    /// The base fee is burnt, and transactions could have dynamic fee (EIP-1559).
+    pub have_base_fee: bool,
+    /// The refund is capped by the gas used divided by it.
+    pub max_refund_quotient: u64,
+}
+
+impl Schedule {
+    pub fn new(fork: Fork) -> Self {
+        let tangerine_whistle = fork >= Fork::TangerineWhistle;
+        let berlin = fork >= Fork::Berlin;
+        let london = fork >= Fork::London;
+        Schedule {
+            fork,
+            have_revert: fork >= Fork::Byzantium,
+            have_delegatecall: fork >= Fork::Homestead,
+            have_staticcall: fork >= Fork::Byzantium,
+            have_return_data: fork >= Fork::Byzantium,
+            have_extcodehash: fork >= Fork::Constantinople,
+            have_create2: fork >= Fork::Constantinople,
+            exp_byte_gas: if fork >= Fork::SpuriousDragon { 50 } else { 10 },
+            sload_gas: if berlin {
+                GAS_WARM_STORAGE_READ
+            } else if fork >= Fork::Istanbul {
+                800
+            } else if tangerine_whistle {
+                200
+            } else {
+                50
+            },
+            balance_gas: if berlin {
+                GAS_WARM_STORAGE_READ
+            } else if fork >= Fork::Istanbul {
+                700
+            } else if tangerine_whistle {
+                400
+            } else {
+                20
+            },
+            extcode_gas: if berlin {
+                GAS_WARM_STORAGE_READ
+            } else if tangerine_whistle {
+                700
+            } else {
+                20
+            },
+            extcodehash_gas: if berlin {
+                GAS_WARM_STORAGE_READ
+            } else if fork >= Fork::Istanbul {
+                700
+            } else {
+                400
+            },
+            call_gas: if berlin {
+                GAS_WARM_STORAGE_READ
+            } else if tangerine_whistle {
+                700
+            } else {
+                40
+            },
+            all_but_one_64th: tangerine_whistle,
+            sstore_set_gas: 20000,
+            sstore_reset_gas: if berlin { 5000 - GAS_COLD_SLOAD } else { 5000 },
+            sstore_clears_refund: if london {
+                5000 - GAS_COLD_SLOAD + GAS_TX_ACCESS_LIST_STORAGE_KEY
+            } else {
+                15000
+            },
+            // EIP-1283 was reverted by Petersburg.
+            net_sstore_metering: fork == Fork::Constantinople || fork >= Fork::Istanbul,
+            sstore_sentry: fork >= Fork::Istanbul,
+            selfdestruct_gas: if tangerine_whistle { 5000 } else { 0 },
+            selfdestruct_new_account_gas: if tangerine_whistle { 25000 } else { 0 },
+            selfdestruct_refund: if london { 0 } else { 24000 },
+            no_empty: fork >= Fork::SpuriousDragon,
+            fail_on_code_deposit: fork >= Fork::Homestead,
+            limit_code_size: fork >= Fork::SpuriousDragon,
+            reject_ef_code: fork >= Fork::London,
+            limit_initcode_size: fork >= Fork::Shanghai,
+            tx_create_gas: if fork >= Fork::Homestead {
+                53000
+            } else {
+                21000
+            },
+            tx_data_non_zero_gas: if fork >= Fork::Istanbul { 16 } else { 68 },
+            have_access_list: berlin,
+            warm_coinbase: fork >= Fork::Shanghai,
+            have_base_fee: fork >= Fork::London,
+            max_refund_quotient: if london { 5 } else { 2 },
+        }
+    }
+}
+
+impl ::std::convert::From<Fork> for Schedule {
+    #[inline]
+    fn from(fork: Fork) -> Self {
+        Schedule::new(fork)
+    }
+}
@@ -6,119 +6,325 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use serde;
+
+use u256::U256;
+
+/// The maximum number of items in the stack by default.
+pub const STACK_LIMIT: usize = 1024;
+
+/// The maximum `n` of `dup` and `swap`, it is fixed by the opcodes `DUP1` ... `DUP16` and
+/// `SWAP1` ... `SWAP16`, so unlike `STACK_LIMIT` it can not be configured.
+pub const DUP_SWAP_LIMIT: usize = 16;
+
+/// The maximum `n` of `dupn` and `swapn` by default, which is the most that the 1-byte immediate
+/// values of `DUPN` and `SWAPN` (EIP-663) could encode.
+pub const DUPN_SWAPN_LIMIT: usize = 256;
+
+/// The maximum of both `n` and `m` of `exchange` by default, which is the most that the two 4-bit
+/// halves of the immediate value of `EXCHANGE` (EIP-663) could encode.
+pub const EXCHANGE_LIMIT: usize = 16;
+
 /// EVM stack.
 ///
-/// In EVM, the stack has a maximum size of 1024, and size of stack items is 256-bit.
+/// In EVM, the stack holds at most `STACK_LIMIT` (1024) items by default, and size of stack
+/// items is 256-bit.
+///
+/// The items are stored on the heap, and the buffer grows as items are pushed, so creating a
+/// stack does not allocate, and cloning a stack only copies the live items.
+///
+/// The limit can be changed by `with_limit`, e.g. for the `stack_limit` of `VmConfig`, and the
+/// limits of the arguments of `dupn`, `swapn` and `exchange` can be lowered by
+/// `set_dupn_swapn_limit` and `set_exchange_limit`.
+///
+/// It is (de)serialized as a list of hexadecimal integers, from the bottom to the top. The limit
+/// is not serialized, a deserialized stack has the same limit as a collected one.
+///
+/// Two stacks are equal if they have the same items, whatever their limits are.
 #[derive(Clone)]
 pub struct Stack {
-    data: [StackItem; 1024],
-    ptr: usize,
-    zeros: StackItem,
+    data: Vec<StackItem>,
+    limit: usize,
+    dupn_swapn_limit: usize,
+    exchange_limit: usize,
 }
 
 /// EVM stack item.
-type StackItem = [u8; 32];
+pub type StackItem = [u8; 32];
+
+/// A stack operation with its argument, to describe which operation failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackOp {
+    /// Push the bytes of the size.
+    Push(usize),
+    Pop,
+    Back(usize),
+    Dup(usize),
+    Swap(usize),
+    DupN(usize),
+    SwapN(usize),
+    Exchange(usize, usize),
+    /// Create a stack of the number of items, by `from_items_with_limit`.
+    FromItems(usize),
+}
+
+impl ::std::fmt::Display for StackOp {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match *self {
+            StackOp::Push(size) => write!(f, "push of {} bytes", size),
+            StackOp::Pop => write!(f, "pop"),
+            StackOp::Back(n) => write!(f, "back({})", n),
+            StackOp::Dup(n) => write!(f, "dup({})", n),
+            StackOp::Swap(n) => write!(f, "swap({})", n),
+            StackOp::DupN(n) => write!(f, "dupn({})", n),
+            StackOp::SwapN(n) => write!(f, "swapn({})", n),
+            StackOp::Exchange(n, m) => write!(f, "exchange({}, {})", n, m),
+            StackOp::FromItems(n) => write!(f, "creation from {} items", n),
+        }
+    }
+}
 
 /// EVM stack errors.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StackError {
-    Underflow,
-    Overflow,
-    Internal,
+    /// The operation requires more items than available.
+    Underflow {
+        op: StackOp,
+        required: usize,
+        available: usize,
+    },
+    /// The stack is full, it holds `limit` items.
+    Overflow { op: StackOp, limit: usize },
+    /// The argument is out of range, e.g. the depth of `dup` or the size of the pushed bytes.
+    InvalidArgument { op: StackOp },
+}
+
+impl StackError {
+    /// The operation which failed.
+    #[inline]
+    pub fn op(&self) -> StackOp {
+        match *self {
+            StackError::Underflow { op, .. }
+            | StackError::Overflow { op, .. }
+            | StackError::InvalidArgument { op } => op,
+        }
+    }
+}
+
+impl ::std::fmt::Display for StackError {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match *self {
+            StackError::Underflow {
+                op,
+                required,
+                available,
+            } => write!(
+                f,
+                "stack underflow in {}: {} required, {} available",
+                op, required, available
+            ),
+            StackError::Overflow { op, limit } => {
+                write!(f, "stack overflow in {}: the limit is {}", op, limit)
+            }
+            StackError::InvalidArgument { op } => write!(f, "invalid argument of {}", op),
+        }
+    }
 }
 
+impl ::std::error::Error for StackError {}
+
 impl ::std::default::Default for Stack {
     #[inline]
     fn default() -> Self {
-        let data = [[0; 32]; 1024];
-        let ptr = 0;
-        let zeros = [0; 32];
-        Self { data, ptr, zeros }
+        Self::with_limit(STACK_LIMIT)
+    }
+}
+
+impl ::std::cmp::PartialEq for Stack {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
     }
 }
 
+impl ::std::cmp::Eq for Stack {}
+
 impl ::std::fmt::Debug for Stack {
+    /// The items from the bottom to the top.
     #[inline]
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
-        writeln!(f)?;
-        writeln!(
-            f,
-            "####################      Stack      ####################"
-        )?;
-        if self.ptr == 0 {
-            writeln!(
-                f,
-                "                     ---- empty ----                     "
-            )?;
-        } else {
-            for (n, i) in (0..self.ptr).rev().enumerate() {
-                write!(f, "{:#05x}+00:", n)?;
-                for v in &(self.data[i])[0..16] {
-                    write!(f, " {:02x}", v)?;
-                }
-                writeln!(f)?;
-                write!(f, "{:#05x}+0f:", n)?;
-                for v in &(self.data[i])[16..32] {
-                    write!(f, " {:02x}", v)?;
-                }
-                writeln!(f)?;
-            }
-        }
-        writeln!(
-            f,
-            "#########################################################"
-        )
+        f.debug_list()
+            .entries(self.data.iter().map(|item| U256::from_be_bytes(*item)))
+            .finish()
     }
 }
 
 impl ::std::fmt::Display for Stack {
     #[inline]
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
-        writeln!(f, "Stack {{ size = {} }}", self.ptr)
+        writeln!(f, "Stack {{ size = {} }}", self.data.len())
+    }
+}
+
+impl ::std::iter::FromIterator<StackItem> for Stack {
+    /// Push the items in order, the last one is the top.
+    ///
+    /// The limit is `STACK_LIMIT`, or the number of the items if there are more, use
+    /// `from_items_with_limit` to check the items against a limit.
+    fn from_iter<I: IntoIterator<Item = StackItem>>(iter: I) -> Self {
+        Self::from_data(iter.into_iter().collect())
+    }
+}
+
+impl serde::Serialize for Stack {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(self.data.iter().map(|item| U256::from_be_bytes(*item)))
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Stack {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let items = Vec::<U256>::deserialize(deserializer)?;
+        Ok(Self::from_data(
+            items.iter().map(U256::to_be_bytes).collect(),
+        ))
     }
 }
 
 impl Stack {
+    /// The limit is `STACK_LIMIT`, or the number of the items if there are more.
+    #[inline]
+    fn from_data(data: Vec<StackItem>) -> Self {
+        let limit = data.len().max(STACK_LIMIT);
+        Self {
+            data,
+            ..Self::with_limit(limit)
+        }
+    }
+
+    /// A stack of the items from the bottom to the top, which holds at most `limit` items.
+    ///
+    /// Returns an overflow error if there are more than `limit` items.
+    pub fn from_items_with_limit<I>(items: I, limit: usize) -> Result<Self, StackError>
+    where
+        I: IntoIterator<Item = StackItem>,
+    {
+        let data = items.into_iter().collect::<Vec<_>>();
+        if data.len() > limit {
+            return Err(StackError::Overflow {
+                op: StackOp::FromItems(data.len()),
+                limit,
+            });
+        }
+        Ok(Self {
+            data,
+            ..Self::with_limit(limit)
+        })
+    }
+
+    /// An empty stack which holds at most `limit` items.
+    #[inline]
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            data: Vec::new(),
+            limit,
+            dupn_swapn_limit: DUPN_SWAPN_LIMIT,
+            exchange_limit: EXCHANGE_LIMIT,
+        }
+    }
+
+    /// The maximum number of items.
+    #[inline]
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// The maximum `n` of `dupn` and `swapn`.
+    #[inline]
+    pub fn dupn_swapn_limit(&self) -> usize {
+        self.dupn_swapn_limit
+    }
+
+    #[inline]
+    pub fn set_dupn_swapn_limit(&mut self, limit: usize) {
+        self.dupn_swapn_limit = limit;
+    }
+
+    /// The maximum of both `n` and `m` of `exchange`.
+    #[inline]
+    pub fn exchange_limit(&self) -> usize {
+        self.exchange_limit
+    }
+
+    #[inline]
+    pub fn set_exchange_limit(&mut self, limit: usize) {
+        self.exchange_limit = limit;
+    }
+
     #[inline]
     pub fn size(&self) -> usize {
-        self.ptr
+        self.data.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// The live items, from the bottom to the top.
+    #[inline]
+    pub fn as_slice(&self) -> &[StackItem] {
+        &self.data[..]
+    }
+
+    /// Iterate over the live items, from the top to the bottom.
+    #[inline]
+    pub fn iter(&self) -> ::std::iter::Rev<::std::slice::Iter<'_, StackItem>> {
+        self.data.iter().rev()
+    }
+
+    /// Iterate over the live items, from the bottom to the top.
+    #[inline]
+    pub fn iter_bottom_first(&self) -> ::std::slice::Iter<'_, StackItem> {
+        self.data.iter()
     }
 
     #[inline]
     pub fn push(&mut self, input: &[u8]) -> Result<(), StackError> {
         let input_length = input.len();
+        let op = StackOp::Push(input_length);
         if input_length > 32 {
-            Err(StackError::Internal)
-        } else if self.ptr >= 1024 {
-            Err(StackError::Overflow)
+            Err(StackError::InvalidArgument { op })
+        } else if self.data.len() >= self.limit {
+            Err(self.overflow(op))
         } else {
-            let data = &mut self.data[self.ptr];
-            (&mut data[32 - input_length..32]).copy_from_slice(&input[..]);
-            self.ptr += 1;
+            let mut data = [0; 32];
+            data[32 - input_length..32].copy_from_slice(input);
+            self.data.push(data);
             Ok(())
         }
     }
 
     #[inline]
     pub fn pop(&mut self) -> Result<[u8; 32], StackError> {
-        if self.ptr == 0 {
-            Err(StackError::Underflow)
-        } else {
-            self.ptr -= 1;
-            let ret = self.data[self.ptr];
-            let data = &mut self.data[self.ptr];
-            (&mut data[..]).copy_from_slice(&self.zeros[..]);
-            Ok(ret)
-        }
+        self.data
+            .pop()
+            .ok_or_else(|| self.underflow(StackOp::Pop, 1))
     }
 
     #[inline]
     pub fn back(&self, n: usize) -> Result<&[u8], StackError> {
-        if self.ptr < n + 1 {
-            Err(StackError::Underflow)
+        let len = self.data.len();
+        if len <= n {
+            Err(self.underflow(StackOp::Back(n), n.saturating_add(1)))
         } else {
-            let data = &self.data[self.ptr - n - 1];
-            Ok(&data[..])
+            Ok(&self.data[len - n - 1][..])
         }
     }
 
@@ -127,36 +333,92 @@ impl Stack {
         self.back(0)
     }
 
+    /// Duplicate the `n`-th item, `n` is in `1..=DUP_SWAP_LIMIT` (`DUP1` ... `DUP16`).
     #[inline]
     pub fn dup(&mut self, n: usize) -> Result<(), StackError> {
-        if 1 > n || n > 16 {
-            Err(StackError::Internal)
-        } else if self.ptr >= 1024 {
-            Err(StackError::Overflow)
-        } else if self.ptr < n {
-            Err(StackError::Underflow)
+        self.dup_within(StackOp::Dup(n), n, DUP_SWAP_LIMIT)
+    }
+
+    /// Swap the top item and the `n + 1`-th item, `n` is in `1..=DUP_SWAP_LIMIT` (`SWAP1` ...
+    /// `SWAP16`).
+    #[inline]
+    pub fn swap(&mut self, n: usize) -> Result<(), StackError> {
+        self.swap_within(StackOp::Swap(n), n, DUP_SWAP_LIMIT)
+    }
+
+    /// Duplicate the `n`-th item, `n` is in `1..=dupn_swapn_limit` (`DUPN`, EIP-663).
+    #[inline]
+    pub fn dupn(&mut self, n: usize) -> Result<(), StackError> {
+        self.dup_within(StackOp::DupN(n), n, self.dupn_swapn_limit)
+    }
+
+    /// Swap the top item and the `n + 1`-th item, `n` is in `1..=dupn_swapn_limit` (`SWAPN`,
+    /// EIP-663).
+    #[inline]
+    pub fn swapn(&mut self, n: usize) -> Result<(), StackError> {
+        self.swap_within(StackOp::SwapN(n), n, self.dupn_swapn_limit)
+    }
+
+    /// Swap the `n + 1`-th item and the `n + m + 1`-th item, both `n` and `m` are in
+    /// `1..=exchange_limit` (`EXCHANGE`, EIP-663).
+    #[inline]
+    pub fn exchange(&mut self, n: usize, m: usize) -> Result<(), StackError> {
+        let len = self.data.len();
+        let op = StackOp::Exchange(n, m);
+        let max = self.exchange_limit;
+        if !(1..=max).contains(&n) || !(1..=max).contains(&m) {
+            Err(StackError::InvalidArgument { op })
+        } else if len < n + m + 1 {
+            Err(self.underflow(op, n + m + 1))
         } else {
-            let (left, right) = self.data.split_at_mut(self.ptr);
-            let x = &mut right[0];
-            let y = &left[self.ptr - n];
-            (x[..]).copy_from_slice(&y[..]);
-            self.ptr += 1;
+            self.data.swap(len - n - 1, len - n - m - 1);
             Ok(())
         }
     }
 
     #[inline]
-    pub fn swap(&mut self, n: usize) -> Result<(), StackError> {
-        if 1 > n || n > 16 {
-            Err(StackError::Internal)
-        } else if self.ptr < n + 1 {
-            Err(StackError::Underflow)
+    fn dup_within(&mut self, op: StackOp, n: usize, max: usize) -> Result<(), StackError> {
+        let len = self.data.len();
+        if !(1..=max).contains(&n) {
+            Err(StackError::InvalidArgument { op })
+        } else if len >= self.limit {
+            Err(self.overflow(op))
+        } else if len < n {
+            Err(self.underflow(op, n))
         } else {
-            let (left, right) = self.data.split_at_mut(self.ptr - 1);
-            let x = &mut right[0];
-            let y = &mut left[self.ptr - n - 1];
-            (x[..]).swap_with_slice(y);
+            let data = self.data[len - n];
+            self.data.push(data);
             Ok(())
         }
     }
+
+    #[inline]
+    fn swap_within(&mut self, op: StackOp, n: usize, max: usize) -> Result<(), StackError> {
+        let len = self.data.len();
+        if !(1..=max).contains(&n) {
+            Err(StackError::InvalidArgument { op })
+        } else if len < n + 1 {
+            Err(self.underflow(op, n + 1))
+        } else {
+            self.data.swap(len - 1, len - n - 1);
+            Ok(())
+        }
+    }
+
+    #[inline]
+    fn underflow(&self, op: StackOp, required: usize) -> StackError {
+        StackError::Underflow {
+            op,
+            required,
+            available: self.data.len(),
+        }
+    }
+
+    #[inline]
+    fn overflow(&self, op: StackOp) -> StackError {
+        StackError::Overflow {
+            op,
+            limit: self.limit,
+        }
+    }
 }
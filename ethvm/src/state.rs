@@ -0,0 +1,299 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! In-memory world state.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use hex;
+#[cfg(feature = "keccak")]
+use keccak;
+#[cfg(feature = "keccak")]
+use rlp::{self, Encodable};
+#[cfg(feature = "keccak")]
+use trie;
+use types::Address;
+#[cfg(feature = "keccak")]
+use types::H256;
+use u256::U256;
+
+/// An account in the world state.
+///
+/// It can be (de)serialized in the same format as the `alloc` field in a genesis file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Account {
+    #[serde(default)]
+    pub balance: U256,
+    #[serde(default, with = "hex::quantity")]
+    pub nonce: u64,
+    #[serde(default, with = "hex::bytes")]
+    pub code: Vec<u8>,
+    #[serde(default)]
+    pub storage: BTreeMap<U256, U256>,
+}
+
+impl Account {
+    /// An account is empty if it has no code, zero nonce and zero balance (EIP-161).
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.code.is_empty() && self.nonce == 0 && self.balance.is_zero()
+    }
+
+    #[cfg(feature = "keccak")]
+    #[inline]
+    pub fn code_hash(&self) -> H256 {
+        keccak::keccak256(&self.code[..])
+    }
+
+    /// The root hash of the storage trie, whose keys are the hashes of the slots.
+    #[cfg(feature = "keccak")]
+    pub fn storage_root(&self) -> H256 {
+        trie::trie_root(
+            self.storage
+                .iter()
+                .map(|(key, value)| (keccak::keccak256(&key.to_be_bytes()), rlp::encode(value))),
+        )
+    }
+}
+
+#[cfg(feature = "keccak")]
+impl Encodable for Account {
+    /// The encoding in the state trie: `[nonce, balance, storage_root, code_hash]`.
+    fn rlp_append(&self, out: &mut Vec<u8>) {
+        let mut payload = Vec::new();
+        self.nonce.rlp_append(&mut payload);
+        self.balance.rlp_append(&mut payload);
+        self.storage_root().rlp_append(&mut payload);
+        self.code_hash().rlp_append(&mut payload);
+        rlp::append_list(out, &payload);
+    }
+}
+
+/// The world state, a map from addresses to accounts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct State {
+    accounts: BTreeMap<Address, Account>,
+}
+
+impl State {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn accounts(&self) -> &BTreeMap<Address, Account> {
+        &self.accounts
+    }
+
+    #[inline]
+    pub fn exists(&self, address: &Address) -> bool {
+        self.accounts.contains_key(address)
+    }
+
+    /// An account is dead if it does not exist or it is empty.
+    #[inline]
+    pub fn is_dead(&self, address: &Address) -> bool {
+        self.accounts
+            .get(address)
+            .map(Account::is_empty)
+            .unwrap_or(true)
+    }
+
+    #[inline]
+    pub fn account(&self, address: &Address) -> Option<&Account> {
+        self.accounts.get(address)
+    }
+
+    /// Get an account for modification, the account will be created if not exist.
+    #[inline]
+    pub fn account_mut(&mut self, address: &Address) -> &mut Account {
+        self.accounts.entry(*address).or_default()
+    }
+
+    #[inline]
+    pub fn insert(&mut self, address: Address, account: Account) -> Option<Account> {
+        self.accounts.insert(address, account)
+    }
+
+    #[inline]
+    pub fn remove(&mut self, address: &Address) -> Option<Account> {
+        self.accounts.remove(address)
+    }
+
+    #[inline]
+    pub fn balance(&self, address: &Address) -> U256 {
+        self.accounts
+            .get(address)
+            .map(|account| account.balance)
+            .unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn nonce(&self, address: &Address) -> u64 {
+        self.accounts
+            .get(address)
+            .map(|account| account.nonce)
+            .unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn code(&self, address: &Address) -> &[u8] {
+        self.accounts
+            .get(address)
+            .map(|account| &account.code[..])
+            .unwrap_or(&[])
+    }
+
+    #[inline]
+    pub fn storage(&self, address: &Address, key: &U256) -> U256 {
+        self.accounts
+            .get(address)
+            .and_then(|account| account.storage.get(key))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Set a storage slot, the slot is removed if the value is zero.
+    #[inline]
+    pub fn set_storage(&mut self, address: &Address, key: U256, value: U256) {
+        let storage = &mut self.account_mut(address).storage;
+        if value.is_zero() {
+            storage.remove(&key);
+        } else {
+            storage.insert(key, value);
+        }
+    }
+
+    /// The root hash of the state trie, whose keys are the hashes of the addresses.
+    #[cfg(feature = "keccak")]
+    pub fn state_root(&self) -> H256 {
+        trie::trie_root(self.accounts.iter().map(|(address, account)| {
+            (keccak::keccak256(address.as_bytes()), rlp::encode(account))
+        }))
+    }
+
+    /// Compare with a newer state.
+    pub fn diff(&self, post: &State) -> StateDiff {
+        let addresses = self
+            .accounts
+            .keys()
+            .chain(post.accounts.keys())
+            .collect::<BTreeSet<_>>();
+        let empty = Account::default();
+        let mut accounts = BTreeMap::new();
+        for address in addresses {
+            let (pre_account, post_account) = (self.account(address), post.account(address));
+            let kind = match (pre_account.is_some(), post_account.is_some()) {
+                (false, true) => AccountDiffKind::Created,
+                (true, false) => AccountDiffKind::Removed,
+                _ => AccountDiffKind::Modified,
+            };
+            let pre_account = pre_account.unwrap_or(&empty);
+            let post_account = post_account.unwrap_or(&empty);
+            if kind == AccountDiffKind::Modified && pre_account == post_account {
+                continue;
+            }
+            let keys = pre_account
+                .storage
+                .keys()
+                .chain(post_account.storage.keys())
+                .collect::<BTreeSet<_>>();
+            let storage = keys
+                .into_iter()
+                .filter_map(|key| {
+                    let pre_value = pre_account.storage.get(key).cloned().unwrap_or_default();
+                    let post_value = post_account.storage.get(key).cloned().unwrap_or_default();
+                    if pre_value == post_value {
+                        None
+                    } else {
+                        Some((*key, (pre_value, post_value)))
+                    }
+                })
+                .collect();
+            let diff = AccountDiff {
+                kind,
+                balance: changed(pre_account.balance, post_account.balance),
+                nonce: changed(pre_account.nonce, post_account.nonce),
+                code: changed(&pre_account.code, &post_account.code)
+                    .map(|(pre, post)| (pre.clone(), post.clone())),
+                storage,
+            };
+            accounts.insert(*address, diff);
+        }
+        StateDiff { accounts }
+    }
+}
+
+#[inline]
+fn changed<T: PartialEq>(pre: T, post: T) -> Option<(T, T)> {
+    if pre == post {
+        None
+    } else {
+        Some((pre, post))
+    }
+}
+
+/// How an account is changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountDiffKind {
+    Created,
+    Modified,
+    Removed,
+}
+
+/// Changes of an account, each changed field is a pair of the old value and the new value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountDiff {
+    pub kind: AccountDiffKind,
+    pub balance: Option<(U256, U256)>,
+    pub nonce: Option<(u64, u64)>,
+    pub code: Option<(Vec<u8>, Vec<u8>)>,
+    pub storage: BTreeMap<U256, (U256, U256)>,
+}
+
+/// Changes between two states.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateDiff {
+    pub accounts: BTreeMap<Address, AccountDiff>,
+}
+
+impl StateDiff {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+}
+
+impl fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (address, diff) in &self.accounts {
+            let kind = match diff.kind {
+                AccountDiffKind::Created => "created",
+                AccountDiffKind::Modified => "modified",
+                AccountDiffKind::Removed => "removed",
+            };
+            writeln!(f, "{} ({})", address, kind)?;
+            if let Some((pre, post)) = diff.balance {
+                writeln!(f, "    balance: {} => {}", pre, post)?;
+            }
+            if let Some((pre, post)) = diff.nonce {
+                writeln!(f, "    nonce: {} => {}", pre, post)?;
+            }
+            if let Some((ref pre, ref post)) = diff.code {
+                writeln!(f, "    code: {} => {}", hex::encode(pre), hex::encode(post))?;
+            }
+            for (key, (pre, post)) in &diff.storage {
+                writeln!(f, "    storage[{:#x}]: {:#x} => {:#x}", key, pre, post)?;
+            }
+        }
+        Ok(())
+    }
+}
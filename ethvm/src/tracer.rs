@@ -0,0 +1,159 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Step tracers, which observe the interpreter instruction by instruction.
+
+use std::io;
+
+use serde_json;
+
+use hex;
+use host::{ExecutionResult, Host};
+use interpreter::{Halt, Interpreter};
+use opcodes;
+use u256::U256;
+
+/// Observe the execution step by step.
+///
+/// The interpreter only pays for tracing when a tracer is installed into the host.
+pub trait Tracer {
+    /// Called before each step, `vm.pc()` points to the opcode which is going to be executed.
+    fn step(&mut self, vm: &Interpreter, host: &Host);
+
+    /// Called after each step, `halt` is `Some` if the step halted the execution.
+    fn step_end(&mut self, vm: &Interpreter, host: &Host, halt: Option<Halt>);
+
+    /// Called when the message call is finished, after the changes of a failed call are
+    /// discarded and the empty accounts touched by it are removed.
+    fn end(&mut self, _host: &Host, _result: &ExecutionResult) {}
+}
+
+/// A step in the format of EIP-3155.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StepLog {
+    pc: usize,
+    op: u8,
+    gas: String,
+    gas_cost: String,
+    mem_size: usize,
+    stack: Vec<String>,
+    return_data: String,
+    depth: usize,
+    refund: u64,
+    op_name: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// The summary in the format of EIP-3155.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SummaryLog {
+    /// It needs the `keccak` feature.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state_root: Option<String>,
+    output: String,
+    gas_used: String,
+    pass: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[cfg(feature = "keccak")]
+fn state_root(host: &Host) -> Option<String> {
+    Some(host.state().state_root().to_string())
+}
+
+#[cfg(not(feature = "keccak"))]
+fn state_root(_host: &Host) -> Option<String> {
+    None
+}
+
+/// Write traces as JSON lines in the format of EIP-3155.
+///
+/// A line is written after the step is executed, since the gas cost is not known before.
+pub struct JsonTracer<W: io::Write> {
+    out: W,
+    /// The step which is being executed, with the gas before it.
+    pending: Option<(u64, StepLog)>,
+}
+
+impl<W: io::Write> JsonTracer<W> {
+    pub fn new(out: W) -> Self {
+        JsonTracer { out, pending: None }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    fn write_line<T: ::serde::Serialize>(&mut self, line: &T) {
+        // Tracing is best-effort, an I/O error should not break the execution.
+        let _ = serde_json::to_writer(&mut self.out, line)
+            .map_err(io::Error::from)
+            .and_then(|_| writeln!(self.out));
+    }
+}
+
+impl<W: io::Write> Tracer for JsonTracer<W> {
+    fn step(&mut self, vm: &Interpreter, host: &Host) {
+        let op = vm.code().get(vm.pc()).cloned().unwrap_or(0);
+        let stack = vm
+            .stack()
+            .as_slice()
+            .iter()
+            .map(|item| format!("{:#x}", U256::from_be_bytes(*item)))
+            .collect();
+        let log = StepLog {
+            pc: vm.pc(),
+            op,
+            gas: format!("{:#x}", vm.gas()),
+            gas_cost: String::new(),
+            mem_size: vm.memory().size(),
+            stack,
+            return_data: hex::encode(vm.return_data()),
+            depth: vm.depth() + 1,
+            refund: host.refund(),
+            op_name: opcodes::mnemonic(op, host.config.eip663).unwrap_or("UNKNOWN"),
+            error: None,
+        };
+        self.pending = Some((vm.gas(), log));
+    }
+
+    fn step_end(&mut self, vm: &Interpreter, _host: &Host, halt: Option<Halt>) {
+        if let Some((gas, mut line)) = self.pending.take() {
+            line.gas_cost = format!("{:#x}", gas.saturating_sub(vm.gas()));
+            line.error = halt
+                .filter(Halt::is_error)
+                .map(|halt| match vm.stack_fault() {
+                    // The location is already in the line.
+                    Some(fault) if Halt::from(fault.error) == halt => fault.error.to_string(),
+                    _ => halt.to_string(),
+                });
+            self.write_line(&line);
+        }
+    }
+
+    fn end(&mut self, host: &Host, result: &ExecutionResult) {
+        let summary = SummaryLog {
+            state_root: state_root(host),
+            output: hex::encode(&result.output)[2..].to_owned(),
+            gas_used: format!("{:#x}", result.gas_used),
+            pass: result.halt.is_success(),
+            error: if result.halt.is_success() {
+                None
+            } else {
+                Some(result.halt.to_string())
+            },
+        };
+        self.write_line(&summary);
+        let _ = self.out.flush();
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Transactions and their receipts.
+
+use std::fmt;
+
+use hex;
+use interpreter::Halt;
+use log::Log;
+use schedule::{
+    Schedule, GAS_INITCODE_WORD, GAS_TX, GAS_TX_ACCESS_LIST_ADDRESS,
+    GAS_TX_ACCESS_LIST_STORAGE_KEY, GAS_TX_DATA_ZERO,
+};
+#[cfg(feature = "keccak")]
+use types::Bloom;
+use types::{Address, H256};
+use u256::U256;
+
+/// An item of the access list of a transaction (EIP-2930).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListItem {
+    pub address: Address,
+    #[serde(default)]
+    pub storage_keys: Vec<H256>,
+}
+
+/// A signed transaction, the signature is already recovered into `sender`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+    pub sender: Address,
+    /// The recipient, `None` for a contract creation.
+    #[serde(default)]
+    pub to: Option<Address>,
+    #[serde(default, with = "hex::quantity")]
+    pub nonce: u64,
+    #[serde(default)]
+    pub value: U256,
+    /// The input data, or the init code for a contract creation.
+    #[serde(default, with = "hex::bytes")]
+    pub data: Vec<u8>,
+    #[serde(with = "hex::quantity")]
+    pub gas_limit: u64,
+    /// The gas price, or the maximum fee per gas if `max_priority_fee_per_gas` is set.
+    #[serde(default)]
+    pub gas_price: U256,
+    /// The maximum priority fee per gas, only for the transactions with dynamic fee (EIP-1559).
+    #[serde(default)]
+    pub max_priority_fee_per_gas: Option<U256>,
+    #[serde(default)]
+    pub access_list: Vec<AccessListItem>,
+}
+
+impl Transaction {
+    #[inline]
+    pub fn is_create(&self) -> bool {
+        self.to.is_none()
+    }
+
+    /// The gas charged before the execution.
+    pub fn intrinsic_gas(&self, schedule: &Schedule) -> u64 {
+        let mut gas = if self.is_create() {
+            schedule.tx_create_gas
+        } else {
+            GAS_TX
+        };
+        let zeros = self.data.iter().filter(|b| **b == 0).count() as u64;
+        let non_zeros = self.data.len() as u64 - zeros;
+        gas += zeros * GAS_TX_DATA_ZERO + non_zeros * schedule.tx_data_non_zero_gas;
+        if self.is_create() && schedule.limit_initcode_size {
+            gas += GAS_INITCODE_WORD * (self.data.len() as u64).div_ceil(32);
+        }
+        for item in &self.access_list {
+            gas += GAS_TX_ACCESS_LIST_ADDRESS;
+            gas += GAS_TX_ACCESS_LIST_STORAGE_KEY * item.storage_keys.len() as u64;
+        }
+        gas
+    }
+}
+
+/// The outcome of an executed transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Receipt {
+    pub halt: Halt,
+    /// The data returned by `RETURN` or `REVERT`, or the code of the new contract.
+    pub output: Vec<u8>,
+    /// The gas paid by the sender, after the refund.
+    pub gas_used: u64,
+    /// The refunded gas.
+    pub gas_refunded: u64,
+    pub logs: Vec<Log>,
+    /// The address of the new contract if a contract creation succeeded.
+    pub contract_address: Option<Address>,
+}
+
+impl Receipt {
+    /// The status code of the receipt (EIP-658).
+    #[inline]
+    pub fn is_success(&self) -> bool {
+        self.halt.is_success()
+    }
+
+    /// The bloom filter of the logs.
+    #[cfg(feature = "keccak")]
+    #[inline]
+    pub fn logs_bloom(&self) -> Bloom {
+        Bloom::from_logs(&self.logs[..])
+    }
+}
+
+/// Reasons why a transaction is invalid, an invalid transaction does not change the state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionError {
+    /// The nonce of the transaction is not the nonce of the sender.
+    NonceMismatch {
+        expected: u64,
+        got: u64,
+    },
+    NonceOverflow,
+    /// The sender can not pay for the gas limit and the value.
+    InsufficientFunds,
+    /// The gas limit is less than the intrinsic gas.
+    IntrinsicGasTooLow {
+        intrinsic: u64,
+        limit: u64,
+    },
+    /// The maximum fee per gas is less than the base fee.
+    FeeCapTooLow,
+    /// The maximum priority fee per gas is greater than the maximum fee per gas.
+    PriorityFeeTooHigh,
+    /// The fork does not support the type of the transaction.
+    UnsupportedType,
+    /// The sender has code (EIP-3607).
+    SenderNotEoa,
+    /// The init code is larger than the limit (EIP-3860).
+    InitCodeSizeExceeded,
+}
+
+impl fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TransactionError::NonceMismatch { expected, got } => {
+                write!(f, "invalid nonce: expected {}, got {}", expected, got)
+            }
+            TransactionError::NonceOverflow => write!(f, "nonce has max value"),
+            TransactionError::InsufficientFunds => {
+                write!(f, "insufficient funds for gas * price + value")
+            }
+            TransactionError::IntrinsicGasTooLow { intrinsic, limit } => write!(
+                f,
+                "intrinsic gas too low: have {}, want {}",
+                limit, intrinsic
+            ),
+            TransactionError::FeeCapTooLow => {
+                write!(f, "max fee per gas less than block base fee")
+            }
+            TransactionError::PriorityFeeTooHigh => {
+                write!(f, "max priority fee per gas higher than max fee per gas")
+            }
+            TransactionError::UnsupportedType => write!(f, "transaction type not supported"),
+            TransactionError::SenderNotEoa => write!(f, "sender not an eoa"),
+            TransactionError::InitCodeSizeExceeded => write!(f, "max initcode size exceeded"),
+        }
+    }
+}
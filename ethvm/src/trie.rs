@@ -0,0 +1,316 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Merkle Patricia trie, which commits to a map of byte strings with a root hash.
+//!
+//! The trie is kept in memory, the nodes are encoded and hashed when the root hash is computed.
+
+use std::mem;
+
+use keccak;
+use rlp;
+use types::H256;
+
+/// The root hash of the empty trie, the hash of the RLP encoding of the empty string.
+pub const EMPTY_ROOT: H256 = H256([
+    0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+    0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+]);
+
+/// A hexary Merkle Patricia trie.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Trie {
+    root: Node,
+}
+
+/// A node, the paths are in nibbles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Empty,
+    Leaf(Vec<u8>, Vec<u8>),
+    /// The child is always a branch.
+    Extension(Vec<u8>, Box<Node>),
+    Branch(Box<[Node; 16]>, Option<Vec<u8>>),
+}
+
+impl Default for Node {
+    #[inline]
+    fn default() -> Self {
+        Node::Empty
+    }
+}
+
+impl Trie {
+    #[inline]
+    pub fn new() -> Self {
+        Trie::default()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.root == Node::Empty
+    }
+
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        let path = nibbles(key);
+        let mut path = &path[..];
+        let mut node = &self.root;
+        loop {
+            match *node {
+                Node::Empty => return None,
+                Node::Leaf(ref leaf_path, ref value) => {
+                    return if leaf_path[..] == *path {
+                        Some(&value[..])
+                    } else {
+                        None
+                    };
+                }
+                Node::Extension(ref shared, ref child) => {
+                    if !path.starts_with(shared) {
+                        return None;
+                    }
+                    path = &path[shared.len()..];
+                    node = child;
+                }
+                Node::Branch(ref children, ref value) => {
+                    if path.is_empty() {
+                        return value.as_ref().map(|value| &value[..]);
+                    }
+                    node = &children[usize::from(path[0])];
+                    path = &path[1..];
+                }
+            }
+        }
+    }
+
+    /// Insert a value, return the old value of the key.
+    ///
+    /// An empty value is not stored, inserting it is the same as removing the key.
+    pub fn insert(&mut self, key: &[u8], value: Vec<u8>) -> Option<Vec<u8>> {
+        if value.is_empty() {
+            return self.remove(key);
+        }
+        let root = mem::take(&mut self.root);
+        let (root, old) = insert(root, &nibbles(key), value);
+        self.root = root;
+        old
+    }
+
+    /// Remove a key, return its value.
+    pub fn remove(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+        let root = mem::take(&mut self.root);
+        let (root, old) = remove(root, &nibbles(key));
+        self.root = root;
+        old
+    }
+
+    /// The root hash, which is `EMPTY_ROOT` for the empty trie.
+    #[inline]
+    pub fn root(&self) -> H256 {
+        keccak::keccak256(&encode(&self.root))
+    }
+}
+
+/// The root hash of the trie of the items.
+///
+/// If a key appears more than once, the last value is taken.
+pub fn trie_root<I, K, V>(items: I) -> H256
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+{
+    let mut trie = Trie::new();
+    for (key, value) in items {
+        trie.insert(key.as_ref(), value.as_ref().to_vec());
+    }
+    trie.root()
+}
+
+/// Split bytes into 4-bit nibbles.
+fn nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|b| vec![b >> 4, b & 0x0f]).collect()
+}
+
+#[inline]
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+/// Put a node under a path, the path is not empty.
+fn with_prefix(mut prefix: Vec<u8>, node: Node) -> Node {
+    match node {
+        Node::Empty => Node::Empty,
+        Node::Leaf(path, value) => {
+            prefix.extend_from_slice(&path);
+            Node::Leaf(prefix, value)
+        }
+        Node::Extension(path, child) => {
+            prefix.extend_from_slice(&path);
+            Node::Extension(prefix, child)
+        }
+        branch => Node::Extension(prefix, Box::new(branch)),
+    }
+}
+
+fn insert(node: Node, path: &[u8], value: Vec<u8>) -> (Node, Option<Vec<u8>>) {
+    match node {
+        Node::Empty => (Node::Leaf(path.to_vec(), value), None),
+        Node::Leaf(leaf_path, old) => {
+            if leaf_path[..] == *path {
+                return (Node::Leaf(leaf_path, value), Some(old));
+            }
+            let shared = common_prefix(&leaf_path, path);
+            let (branch, _) = insert(
+                Node::Branch(Default::default(), None),
+                &leaf_path[shared..],
+                old,
+            );
+            let (branch, _) = insert(branch, &path[shared..], value);
+            if shared == 0 {
+                (branch, None)
+            } else {
+                (with_prefix(path[..shared].to_vec(), branch), None)
+            }
+        }
+        Node::Extension(ext_path, child) => {
+            let shared = common_prefix(&ext_path, path);
+            if shared == ext_path.len() {
+                let (child, old) = insert(*child, &path[shared..], value);
+                return (Node::Extension(ext_path, Box::new(child)), old);
+            }
+            // Split the extension at the first different nibble.
+            let mut children: [Node; 16] = Default::default();
+            children[usize::from(ext_path[shared])] = if shared + 1 == ext_path.len() {
+                *child
+            } else {
+                Node::Extension(ext_path[shared + 1..].to_vec(), child)
+            };
+            let branch = Node::Branch(Box::new(children), None);
+            let (branch, _) = insert(branch, &path[shared..], value);
+            if shared == 0 {
+                (branch, None)
+            } else {
+                (with_prefix(path[..shared].to_vec(), branch), None)
+            }
+        }
+        Node::Branch(mut children, branch_value) => {
+            if path.is_empty() {
+                return (Node::Branch(children, Some(value)), branch_value);
+            }
+            let index = usize::from(path[0]);
+            let child = mem::take(&mut children[index]);
+            let (child, old) = insert(child, &path[1..], value);
+            children[index] = child;
+            (Node::Branch(children, branch_value), old)
+        }
+    }
+}
+
+fn remove(node: Node, path: &[u8]) -> (Node, Option<Vec<u8>>) {
+    match node {
+        Node::Empty => (Node::Empty, None),
+        Node::Leaf(leaf_path, value) => {
+            if leaf_path[..] == *path {
+                (Node::Empty, Some(value))
+            } else {
+                (Node::Leaf(leaf_path, value), None)
+            }
+        }
+        Node::Extension(ext_path, child) => {
+            if !path.starts_with(&ext_path) {
+                return (Node::Extension(ext_path, child), None);
+            }
+            match remove(*child, &path[ext_path.len()..]) {
+                (child, None) => (Node::Extension(ext_path, Box::new(child)), None),
+                (child, old) => (with_prefix(ext_path, child), old),
+            }
+        }
+        Node::Branch(mut children, mut value) => {
+            let old = if path.is_empty() {
+                value.take()
+            } else {
+                let index = usize::from(path[0]);
+                let child = mem::take(&mut children[index]);
+                let (child, old) = remove(child, &path[1..]);
+                children[index] = child;
+                old
+            };
+            if old.is_none() {
+                return (Node::Branch(children, value), None);
+            }
+            // A branch with only one child or only the value is collapsed.
+            let mut rest = children
+                .iter()
+                .enumerate()
+                .filter(|(_, child)| **child != Node::Empty)
+                .map(|(index, _)| index);
+            let node = match (rest.next(), rest.next(), value) {
+                (None, _, Some(value)) => Node::Leaf(Vec::new(), value),
+                (Some(index), None, None) => {
+                    let child = mem::take(&mut children[index]);
+                    with_prefix(vec![index as u8], child)
+                }
+                (_, _, value) => Node::Branch(children, value),
+            };
+            (node, old)
+        }
+    }
+}
+
+/// The hex-prefix encoding of a path of nibbles, with the flag of leaf nodes.
+fn hex_prefix(path: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 2 } else { 0 };
+    let mut ret = Vec::with_capacity(path.len() / 2 + 1);
+    let rest = if path.len() % 2 == 1 {
+        ret.push(((flag + 1) << 4) | path[0]);
+        &path[1..]
+    } else {
+        ret.push(flag << 4);
+        path
+    };
+    for pair in rest.chunks(2) {
+        ret.push((pair[0] << 4) | pair[1]);
+    }
+    ret
+}
+
+/// The reference to a node in its parent: the node itself if its encoding is shorter than 32
+/// bytes, otherwise the hash of it.
+fn append_reference(out: &mut Vec<u8>, node: &Node) {
+    let encoded = encode(node);
+    if encoded.len() < 32 {
+        out.extend_from_slice(&encoded);
+    } else {
+        rlp::append_bytes(out, keccak::keccak256(&encoded).as_bytes());
+    }
+}
+
+fn encode(node: &Node) -> Vec<u8> {
+    let mut payload = Vec::new();
+    match *node {
+        Node::Empty => return vec![0x80],
+        Node::Leaf(ref path, ref value) => {
+            rlp::append_bytes(&mut payload, &hex_prefix(path, true));
+            rlp::append_bytes(&mut payload, value);
+        }
+        Node::Extension(ref path, ref child) => {
+            rlp::append_bytes(&mut payload, &hex_prefix(path, false));
+            append_reference(&mut payload, child);
+        }
+        Node::Branch(ref children, ref value) => {
+            for child in children.iter() {
+                append_reference(&mut payload, child);
+            }
+            rlp::append_bytes(&mut payload, value.as_ref().map_or(&[], |value| &value[..]));
+        }
+    }
+    let mut out = Vec::with_capacity(payload.len() + 9);
+    rlp::append_list(&mut out, &payload);
+    out
+}
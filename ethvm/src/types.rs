@@ -0,0 +1,202 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Fixed-size byte arrays used in EVM: addresses and 256-bit hashes.
+
+use std::{fmt, str};
+
+use serde;
+
+use hex;
+#[cfg(feature = "keccak")]
+use keccak;
+#[cfg(feature = "keccak")]
+use rlp::{self, Encodable};
+use u256::U256;
+
+macro_rules! construct_fixed_bytes {
+    ($(#[$attr:meta])* $name:ident, $size:expr) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(pub [u8; $size]);
+
+        impl ::std::default::Default for $name {
+            #[inline]
+            fn default() -> Self {
+                $name([0u8; $size])
+            }
+        }
+
+        impl $name {
+            pub const SIZE: usize = $size;
+
+            /// Create from a slice, the slice should be exactly `SIZE` bytes.
+            #[inline]
+            pub fn from_slice(slice: &[u8]) -> Self {
+                let mut ret = [0u8; $size];
+                ret.copy_from_slice(slice);
+                $name(ret)
+            }
+
+            #[inline]
+            pub fn as_bytes(&self) -> &[u8] {
+                &self.0[..]
+            }
+
+            #[inline]
+            pub fn is_zero(&self) -> bool {
+                self.0.iter().all(|b| *b == 0)
+            }
+        }
+
+        impl ::std::convert::AsRef<[u8]> for $name {
+            #[inline]
+            fn as_ref(&self) -> &[u8] {
+                &self.0[..]
+            }
+        }
+
+        impl ::std::convert::From<[u8; $size]> for $name {
+            #[inline]
+            fn from(bytes: [u8; $size]) -> Self {
+                $name(bytes)
+            }
+        }
+
+        impl fmt::Debug for $name {
+            #[inline]
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", hex::encode(&self.0[..]))
+            }
+        }
+
+        impl fmt::Display for $name {
+            #[inline]
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", hex::encode(&self.0[..]))
+            }
+        }
+
+        impl str::FromStr for $name {
+            type Err = hex::FromHexError;
+            /// Parse from a hexadecimal string, the `0x` prefix is optional.
+            #[inline]
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let bytes = hex::decode(s)?;
+                if bytes.len() != $size {
+                    return Err(hex::FromHexError::BadSize);
+                }
+                Ok($name::from_slice(&bytes[..]))
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serializer.serialize_str(&hex::encode(&self.0[..]))
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                struct Visitor;
+                impl<'b> serde::de::Visitor<'b> for Visitor {
+                    type Value = $name;
+                    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                        write!(f, "a hexadecimal string of {} bytes", $size)
+                    }
+                    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        v.parse().map_err(|err| {
+                            E::custom(format!("bad {} {:?}: {:?}", stringify!($name), v, err))
+                        })
+                    }
+                }
+                deserializer.deserialize_str(Visitor)
+            }
+        }
+    };
+}
+
+construct_fixed_bytes!(
+    /// 160-bit address of an account.
+    Address,
+    20
+);
+
+construct_fixed_bytes!(
+    /// 256-bit hash, or any 32-byte word which is not an integer.
+    H256,
+    32
+);
+
+construct_fixed_bytes!(
+    /// 2048-bit bloom filter of logs.
+    Bloom,
+    256
+);
+
+impl ::std::convert::From<U256> for Address {
+    /// Take the lower 160 bits.
+    #[inline]
+    fn from(v: U256) -> Self {
+        Address::from_slice(&v.to_be_bytes()[12..])
+    }
+}
+
+impl ::std::convert::From<Address> for U256 {
+    #[inline]
+    fn from(address: Address) -> Self {
+        U256::from_be_slice(&address.0[..])
+    }
+}
+
+impl ::std::convert::From<U256> for H256 {
+    #[inline]
+    fn from(v: U256) -> Self {
+        H256(v.to_be_bytes())
+    }
+}
+
+impl ::std::convert::From<H256> for U256 {
+    #[inline]
+    fn from(hash: H256) -> Self {
+        U256::from_be_bytes(hash.0)
+    }
+}
+
+#[cfg(feature = "keccak")]
+impl Address {
+    /// The address of a contract created by `CREATE`: the hash of the RLP encoding of
+    /// `[sender, nonce]`.
+    pub fn from_create(sender: &Address, nonce: u64) -> Self {
+        let mut payload = Vec::with_capacity(30);
+        sender.rlp_append(&mut payload);
+        nonce.rlp_append(&mut payload);
+        let mut stream = Vec::with_capacity(31);
+        rlp::append_list(&mut stream, &payload);
+        Address::from_slice(&keccak::keccak256(&stream[..]).as_bytes()[12..])
+    }
+
+    /// The address of a contract created by `CREATE2` (EIP-1014).
+    pub fn from_create2(sender: &Address, salt: &H256, init_code: &[u8]) -> Self {
+        let mut hasher = keccak::Keccak256::new();
+        hasher.update(&[0xff]);
+        hasher.update(sender.as_bytes());
+        hasher.update(salt.as_bytes());
+        hasher.update(keccak::keccak256(init_code).as_bytes());
+        Address::from_slice(&hasher.finalize().as_bytes()[12..])
+    }
+}
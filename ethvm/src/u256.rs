@@ -0,0 +1,705 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::cmp::Ordering;
+use std::{fmt, ops, str};
+
+use serde;
+
+/// 256-bit unsigned integer.
+///
+/// The limbs are stored in little-endian order, and all arithmetic follows the EVM semantics:
+/// it wraps around modulo 2^256, and the signed variants treat the value as two's complement.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct U256(pub [u64; 4]);
+
+/// Errors when parse `U256` from a string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FromStrError {
+    Empty,
+    BadCharAt(usize),
+    Overflow,
+}
+
+impl U256 {
+    pub const ZERO: U256 = U256([0, 0, 0, 0]);
+    pub const ONE: U256 = U256([1, 0, 0, 0]);
+    pub const MAX: U256 = U256([!0, !0, !0, !0]);
+
+    /// Create from a big-endian byte slice, the slice should not be longer than 32 bytes.
+    #[inline]
+    pub fn from_be_slice(slice: &[u8]) -> Self {
+        assert!(slice.len() <= 32);
+        let mut bytes = [0u8; 32];
+        bytes[32 - slice.len()..].copy_from_slice(slice);
+        U256::from_be_bytes(bytes)
+    }
+
+    #[inline]
+    pub fn from_be_bytes(bytes: [u8; 32]) -> Self {
+        let mut ret = [0u64; 4];
+        for (i, limb) in ret.iter_mut().enumerate() {
+            let start = 24 - i * 8;
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(&bytes[start..start + 8]);
+            *limb = u64::from_be_bytes(buf);
+        }
+        U256(ret)
+    }
+
+    #[inline]
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        let mut ret = [0u8; 32];
+        for (i, limb) in self.0.iter().enumerate() {
+            let start = 24 - i * 8;
+            ret[start..start + 8].copy_from_slice(&limb.to_be_bytes());
+        }
+        ret
+    }
+
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    #[inline]
+    pub fn low_u64(&self) -> u64 {
+        self.0[0]
+    }
+
+    /// Convert to `u64` if the value fits.
+    #[inline]
+    pub fn to_u64(&self) -> Option<u64> {
+        if self.0[1] == 0 && self.0[2] == 0 && self.0[3] == 0 {
+            Some(self.0[0])
+        } else {
+            None
+        }
+    }
+
+    /// Convert to `usize` if the value fits.
+    #[inline]
+    pub fn to_usize(&self) -> Option<usize> {
+        self.to_u64().and_then(|v| {
+            if v > usize::MAX as u64 {
+                None
+            } else {
+                Some(v as usize)
+            }
+        })
+    }
+
+    /// The count of the significant bits.
+    #[inline]
+    pub fn bits(&self) -> usize {
+        256 - self.leading_zeros()
+    }
+
+    #[inline]
+    pub fn leading_zeros(&self) -> usize {
+        for i in (0..4).rev() {
+            if self.0[i] != 0 {
+                return (3 - i) * 64 + self.0[i].leading_zeros() as usize;
+            }
+        }
+        256
+    }
+
+    /// Test the `n`-th bit, counting from the least significant bit.
+    #[inline]
+    pub fn bit(&self, n: usize) -> bool {
+        n < 256 && (self.0[n / 64] >> (n % 64)) & 1 == 1
+    }
+
+    /// Get the `n`-th byte, counting from the most significant byte.
+    #[inline]
+    pub fn byte(&self, n: usize) -> u8 {
+        if n < 32 {
+            let limb = self.0[3 - n / 8];
+            (limb >> ((7 - n % 8) * 8)) as u8
+        } else {
+            0
+        }
+    }
+
+    #[inline]
+    pub fn overflowing_add(self, other: U256) -> (U256, bool) {
+        let mut ret = [0u64; 4];
+        let mut carry = false;
+        for (i, limb) in ret.iter_mut().enumerate() {
+            let (v, c1) = self.0[i].overflowing_add(other.0[i]);
+            let (v, c2) = v.overflowing_add(carry as u64);
+            *limb = v;
+            carry = c1 || c2;
+        }
+        (U256(ret), carry)
+    }
+
+    #[inline]
+    pub fn overflowing_sub(self, other: U256) -> (U256, bool) {
+        let mut ret = [0u64; 4];
+        let mut borrow = false;
+        for (i, limb) in ret.iter_mut().enumerate() {
+            let (v, b1) = self.0[i].overflowing_sub(other.0[i]);
+            let (v, b2) = v.overflowing_sub(borrow as u64);
+            *limb = v;
+            borrow = b1 || b2;
+        }
+        (U256(ret), borrow)
+    }
+
+    #[inline]
+    pub fn wrapping_add(self, other: U256) -> U256 {
+        self.overflowing_add(other).0
+    }
+
+    #[inline]
+    pub fn wrapping_sub(self, other: U256) -> U256 {
+        self.overflowing_sub(other).0
+    }
+
+    #[inline]
+    pub fn checked_add(self, other: U256) -> Option<U256> {
+        match self.overflowing_add(other) {
+            (_, true) => None,
+            (v, false) => Some(v),
+        }
+    }
+
+    #[inline]
+    pub fn checked_sub(self, other: U256) -> Option<U256> {
+        match self.overflowing_sub(other) {
+            (_, true) => None,
+            (v, false) => Some(v),
+        }
+    }
+
+    #[inline]
+    pub fn saturating_sub(self, other: U256) -> U256 {
+        self.checked_sub(other).unwrap_or(U256::ZERO)
+    }
+
+    /// The full 512-bit product, in little-endian limbs.
+    #[inline]
+    pub fn full_mul(self, other: U256) -> [u64; 8] {
+        let mut ret = [0u64; 8];
+        for i in 0..4 {
+            let mut carry = 0u128;
+            let a = u128::from(self.0[i]);
+            for j in 0..4 {
+                let t = a * u128::from(other.0[j]) + u128::from(ret[i + j]) + carry;
+                ret[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            ret[i + 4] = carry as u64;
+        }
+        ret
+    }
+
+    #[inline]
+    pub fn overflowing_mul(self, other: U256) -> (U256, bool) {
+        let full = self.full_mul(other);
+        let overflow = full[4..].iter().any(|limb| *limb != 0);
+        (U256([full[0], full[1], full[2], full[3]]), overflow)
+    }
+
+    #[inline]
+    pub fn wrapping_mul(self, other: U256) -> U256 {
+        self.overflowing_mul(other).0
+    }
+
+    #[inline]
+    pub fn checked_mul(self, other: U256) -> Option<U256> {
+        match self.overflowing_mul(other) {
+            (_, true) => None,
+            (v, false) => Some(v),
+        }
+    }
+
+    /// The quotient and the remainder, panics if `other` is zero.
+    #[inline]
+    pub fn div_rem(self, other: U256) -> (U256, U256) {
+        let num = [self.0[0], self.0[1], self.0[2], self.0[3], 0, 0, 0, 0];
+        let (q, r) = div_rem_wide(num, other);
+        (U256([q[0], q[1], q[2], q[3]]), r)
+    }
+
+    #[inline]
+    pub fn wrapping_neg(self) -> U256 {
+        (!self).wrapping_add(U256::ONE)
+    }
+
+    /// Whether the value is negative when treated as two's complement.
+    #[inline]
+    pub fn is_negative(&self) -> bool {
+        self.0[3] >> 63 == 1
+    }
+
+    #[inline]
+    fn abs(self) -> U256 {
+        if self.is_negative() {
+            self.wrapping_neg()
+        } else {
+            self
+        }
+    }
+
+    /// Unsigned division as `DIV`, the result is zero if the divisor is zero.
+    #[inline]
+    pub fn evm_div(self, other: U256) -> U256 {
+        if other.is_zero() {
+            U256::ZERO
+        } else {
+            self.div_rem(other).0
+        }
+    }
+
+    /// Unsigned modulo as `MOD`, the result is zero if the divisor is zero.
+    #[inline]
+    pub fn evm_rem(self, other: U256) -> U256 {
+        if other.is_zero() {
+            U256::ZERO
+        } else {
+            self.div_rem(other).1
+        }
+    }
+
+    /// Signed division as `SDIV`, the result is zero if the divisor is zero.
+    #[inline]
+    pub fn signed_div(self, other: U256) -> U256 {
+        if other.is_zero() {
+            return U256::ZERO;
+        }
+        let negative = self.is_negative() ^ other.is_negative();
+        let q = self.abs().div_rem(other.abs()).0;
+        if negative {
+            q.wrapping_neg()
+        } else {
+            q
+        }
+    }
+
+    /// Signed modulo as `SMOD`, the sign of the result follows the dividend.
+    #[inline]
+    pub fn signed_rem(self, other: U256) -> U256 {
+        if other.is_zero() {
+            return U256::ZERO;
+        }
+        let r = self.abs().div_rem(other.abs()).1;
+        if self.is_negative() {
+            r.wrapping_neg()
+        } else {
+            r
+        }
+    }
+
+    /// `(self + other) % modulus` without the intermediate overflow, zero if `modulus` is zero.
+    #[inline]
+    pub fn add_mod(self, other: U256, modulus: U256) -> U256 {
+        if modulus.is_zero() {
+            return U256::ZERO;
+        }
+        let (sum, carry) = self.overflowing_add(other);
+        let num = [
+            sum.0[0],
+            sum.0[1],
+            sum.0[2],
+            sum.0[3],
+            carry as u64,
+            0,
+            0,
+            0,
+        ];
+        div_rem_wide(num, modulus).1
+    }
+
+    /// `(self * other) % modulus` without the intermediate overflow, zero if `modulus` is zero.
+    #[inline]
+    pub fn mul_mod(self, other: U256, modulus: U256) -> U256 {
+        if modulus.is_zero() {
+            return U256::ZERO;
+        }
+        div_rem_wide(self.full_mul(other), modulus).1
+    }
+
+    /// Exponentiation modulo 2^256.
+    #[inline]
+    pub fn wrapping_pow(self, exponent: U256) -> U256 {
+        let mut base = self;
+        let mut ret = U256::ONE;
+        for i in 0..exponent.bits() {
+            if exponent.bit(i) {
+                ret = ret.wrapping_mul(base);
+            }
+            base = base.wrapping_mul(base);
+        }
+        ret
+    }
+
+    /// Extend the sign from the `(b + 1)`-th byte, counting from the least significant byte.
+    #[inline]
+    pub fn sign_extend(self, b: U256) -> U256 {
+        match b.to_u64() {
+            Some(b) if b < 31 => {
+                let bit = (b as usize) * 8 + 7;
+                let mask = (U256::ONE << (bit + 1)).wrapping_sub(U256::ONE);
+                if self.bit(bit) {
+                    self | !mask
+                } else {
+                    self & mask
+                }
+            }
+            _ => self,
+        }
+    }
+
+    /// Signed comparison, treat both values as two's complement.
+    #[inline]
+    pub fn signed_cmp(&self, other: &U256) -> Ordering {
+        match (self.is_negative(), other.is_negative()) {
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            _ => self.cmp(other),
+        }
+    }
+
+    /// Parse from a decimal string.
+    pub fn from_dec_str(s: &str) -> Result<U256, FromStrError> {
+        if s.is_empty() {
+            return Err(FromStrError::Empty);
+        }
+        let ten = U256::from(10u64);
+        let mut ret = U256::ZERO;
+        for (idx, chr) in s.bytes().enumerate() {
+            let v = match chr {
+                b'0'..=b'9' => chr - b'0',
+                _ => return Err(FromStrError::BadCharAt(idx)),
+            };
+            ret = ret
+                .checked_mul(ten)
+                .and_then(|r| r.checked_add(U256::from(u64::from(v))))
+                .ok_or(FromStrError::Overflow)?;
+        }
+        Ok(ret)
+    }
+
+    /// Parse from a hexadecimal string without the `0x` prefix.
+    pub fn from_hex_str(s: &str) -> Result<U256, FromStrError> {
+        if s.is_empty() {
+            return Err(FromStrError::Empty);
+        }
+        let s = s.trim_start_matches('0');
+        if s.len() > 64 {
+            return Err(FromStrError::Overflow);
+        }
+        let mut ret = U256::ZERO;
+        for (idx, chr) in s.bytes().enumerate() {
+            let v = match chr {
+                b'a'..=b'f' => chr - b'a' + 10,
+                b'A'..=b'F' => chr - b'A' + 10,
+                b'0'..=b'9' => chr - b'0',
+                _ => return Err(FromStrError::BadCharAt(idx)),
+            };
+            ret = (ret << 4) | U256::from(u64::from(v));
+        }
+        Ok(ret)
+    }
+}
+
+/// Divide a 512-bit number by a 256-bit number, with the Knuth's algorithm D.
+fn div_rem_wide(num: [u64; 8], den: U256) -> ([u64; 8], U256) {
+    let n = match den.0.iter().rposition(|limb| *limb != 0) {
+        Some(pos) => pos + 1,
+        None => panic!("attempt to divide by zero"),
+    };
+    let m_n = match num.iter().rposition(|limb| *limb != 0) {
+        Some(pos) => pos + 1,
+        None => return ([0; 8], U256::ZERO),
+    };
+    let mut q = [0u64; 8];
+    if m_n < n {
+        return (q, U256([num[0], num[1], num[2], num[3]]));
+    }
+    if n == 1 {
+        let d = u128::from(den.0[0]);
+        let mut r = 0u128;
+        for j in (0..m_n).rev() {
+            let t = (r << 64) | u128::from(num[j]);
+            q[j] = (t / d) as u64;
+            r = t % d;
+        }
+        return (q, U256([r as u64, 0, 0, 0]));
+    }
+    let s = den.0[n - 1].leading_zeros();
+    let mut vn = [0u64; 4];
+    let mut un = [0u64; 9];
+    if s == 0 {
+        vn[..n].copy_from_slice(&den.0[..n]);
+        un[..m_n].copy_from_slice(&num[..m_n]);
+    } else {
+        for i in (1..n).rev() {
+            vn[i] = (den.0[i] << s) | (den.0[i - 1] >> (64 - s));
+        }
+        vn[0] = den.0[0] << s;
+        un[m_n] = num[m_n - 1] >> (64 - s);
+        for i in (1..m_n).rev() {
+            un[i] = (num[i] << s) | (num[i - 1] >> (64 - s));
+        }
+        un[0] = num[0] << s;
+    }
+    let b = 1u128 << 64;
+    let vtop = u128::from(vn[n - 1]);
+    let vnext = u128::from(vn[n - 2]);
+    for j in (0..=(m_n - n)).rev() {
+        let t = (u128::from(un[j + n]) << 64) | u128::from(un[j + n - 1]);
+        let mut qhat = t / vtop;
+        let mut rhat = t % vtop;
+        while qhat >= b || qhat * vnext > ((rhat << 64) | u128::from(un[j + n - 2])) {
+            qhat -= 1;
+            rhat += vtop;
+            if rhat >= b {
+                break;
+            }
+        }
+        let mut k = 0i128;
+        for i in 0..n {
+            let p = qhat * u128::from(vn[i]);
+            let t = i128::from(un[i + j]) - k - (p & 0xffff_ffff_ffff_ffff) as i128;
+            un[i + j] = t as u64;
+            k = (p >> 64) as i128 - (t >> 64);
+        }
+        let t = i128::from(un[j + n]) - k;
+        un[j + n] = t as u64;
+        if t < 0 {
+            q[j] = (qhat as u64).wrapping_sub(1);
+            let mut carry = 0u128;
+            for i in 0..n {
+                let t = u128::from(un[i + j]) + u128::from(vn[i]) + carry;
+                un[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            un[j + n] = un[j + n].wrapping_add(carry as u64);
+        } else {
+            q[j] = qhat as u64;
+        }
+    }
+    let mut r = [0u64; 4];
+    if s == 0 {
+        r[..n].copy_from_slice(&un[..n]);
+    } else {
+        for i in 0..n {
+            r[i] = (un[i] >> s) | (un[i + 1] << (64 - s));
+        }
+    }
+    (q, U256(r))
+}
+
+impl ::std::convert::From<u64> for U256 {
+    #[inline]
+    fn from(v: u64) -> Self {
+        U256([v, 0, 0, 0])
+    }
+}
+
+impl ::std::convert::From<[u8; 32]> for U256 {
+    #[inline]
+    fn from(bytes: [u8; 32]) -> Self {
+        U256::from_be_bytes(bytes)
+    }
+}
+
+impl ::std::convert::From<U256> for [u8; 32] {
+    #[inline]
+    fn from(v: U256) -> Self {
+        v.to_be_bytes()
+    }
+}
+
+impl ::std::cmp::Ord for U256 {
+    #[inline]
+    fn cmp(&self, other: &U256) -> Ordering {
+        for i in (0..4).rev() {
+            match self.0[i].cmp(&other.0[i]) {
+                Ordering::Equal => {}
+                ord => return ord,
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+impl ::std::cmp::PartialOrd for U256 {
+    #[inline]
+    fn partial_cmp(&self, other: &U256) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl ops::Not for U256 {
+    type Output = U256;
+    #[inline]
+    fn not(self) -> U256 {
+        U256([!self.0[0], !self.0[1], !self.0[2], !self.0[3]])
+    }
+}
+
+macro_rules! impl_bit_op {
+    ($trait:ident, $func:ident, $op:tt) => {
+        impl ops::$trait for U256 {
+            type Output = U256;
+            #[inline]
+            fn $func(self, other: U256) -> U256 {
+                U256([
+                    self.0[0] $op other.0[0],
+                    self.0[1] $op other.0[1],
+                    self.0[2] $op other.0[2],
+                    self.0[3] $op other.0[3],
+                ])
+            }
+        }
+    };
+}
+
+impl_bit_op!(BitAnd, bitand, &);
+impl_bit_op!(BitOr, bitor, |);
+impl_bit_op!(BitXor, bitxor, ^);
+
+impl ops::Shl<usize> for U256 {
+    type Output = U256;
+    #[inline]
+    fn shl(self, shift: usize) -> U256 {
+        if shift >= 256 {
+            return U256::ZERO;
+        }
+        let (limbs, bits) = (shift / 64, shift % 64);
+        let mut ret = [0u64; 4];
+        for (i, limb) in ret.iter_mut().enumerate().skip(limbs) {
+            *limb = self.0[i - limbs] << bits;
+            if bits > 0 && i > limbs {
+                *limb |= self.0[i - limbs - 1] >> (64 - bits);
+            }
+        }
+        U256(ret)
+    }
+}
+
+impl ops::Shr<usize> for U256 {
+    type Output = U256;
+    #[inline]
+    fn shr(self, shift: usize) -> U256 {
+        if shift >= 256 {
+            return U256::ZERO;
+        }
+        let (limbs, bits) = (shift / 64, shift % 64);
+        let mut ret = [0u64; 4];
+        for (i, limb) in ret.iter_mut().enumerate().take(4 - limbs) {
+            *limb = self.0[i + limbs] >> bits;
+            if bits > 0 && i + limbs < 3 {
+                *limb |= self.0[i + limbs + 1] << (64 - bits);
+            }
+        }
+        U256(ret)
+    }
+}
+
+impl fmt::Display for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return f.pad_integral(true, "", "0");
+        }
+        let chunk = U256::from(10_000_000_000_000_000_000u64);
+        let mut chunks = Vec::new();
+        let mut v = *self;
+        while !v.is_zero() {
+            let (q, r) = v.div_rem(chunk);
+            chunks.push(r.low_u64());
+            v = q;
+        }
+        let mut s = chunks.pop().unwrap().to_string();
+        for c in chunks.iter().rev() {
+            s.push_str(&format!("{:019}", c));
+        }
+        f.pad_integral(true, "", &s)
+    }
+}
+
+impl fmt::LowerHex for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = String::new();
+        for limb in self.0.iter().rev() {
+            if s.is_empty() {
+                if *limb != 0 {
+                    s = format!("{:x}", limb);
+                }
+            } else {
+                s.push_str(&format!("{:016x}", limb));
+            }
+        }
+        if s.is_empty() {
+            s.push('0');
+        }
+        f.pad_integral(true, "0x", &s)
+    }
+}
+
+impl fmt::Debug for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#x}", self)
+    }
+}
+
+impl str::FromStr for U256 {
+    type Err = FromStrError;
+    /// Parse from a decimal string, or a hexadecimal string with the `0x` prefix.
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("0x") || s.starts_with("0X") {
+            U256::from_hex_str(&s[2..])
+        } else {
+            U256::from_dec_str(s)
+        }
+    }
+}
+
+impl serde::Serialize for U256 {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&format!("{:#x}", self))
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for U256 {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+        impl<'b> serde::de::Visitor<'b> for Visitor {
+            type Value = U256;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a 256-bit unsigned integer, in a number or a string")
+            }
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(U256::from(v))
+            }
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                v.parse()
+                    .map_err(|err| E::custom(format!("bad U256 {:?}: {:?}", v, err)))
+            }
+        }
+        deserializer.deserialize_any(Visitor)
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate ethvm;
+
+mod common;
+
+use common::{address, message, new_state, run, u};
+use ethvm::host::{ExecutionResult, Host};
+use ethvm::interpreter::Halt;
+use ethvm::schedule::Fork;
+use ethvm::state::State;
+use ethvm::transaction::{AccessListItem, Transaction};
+use ethvm::types::H256;
+
+/// Call the contract at `0x10` from `0x01`.
+fn execute(fork: Fork, contracts: &[(u64, &str)]) -> (ExecutionResult, State) {
+    let host = Host::new(fork, new_state(contracts));
+    run(host, message(0, Vec::new(), 100_000))
+}
+
+#[test]
+fn storage() {
+    // pop(sload(0)); pop(sload(0))
+    let code = "60005450 60005450 00";
+    let (result, _) = execute(Fork::Berlin, &[(0x10, code)]);
+    assert_eq!(result.gas_used, 3 + 2100 + 2 + 3 + 100 + 2);
+    let (result, _) = execute(Fork::Istanbul, &[(0x10, code)]);
+    assert_eq!(result.gas_used, 3 + 800 + 2 + 3 + 800 + 2);
+    // sstore(0, 1); pop(sload(0))
+    let code = "6001600055 60005450 00";
+    let (result, _) = execute(Fork::Berlin, &[(0x10, code)]);
+    assert_eq!(result.gas_used, 3 + 3 + 20000 + 2100 + 3 + 100 + 2);
+}
+
+#[test]
+fn accounts() {
+    // pop(balance(0x20)); pop(balance(0x20))
+    let code = "60203150 60203150 00";
+    let (result, _) = execute(Fork::Berlin, &[(0x10, code)]);
+    assert_eq!(result.gas_used, 3 + 2600 + 2 + 3 + 100 + 2);
+    // pop(extcodesize(0x20)); pop(balance(0x20))
+    let code = "60203b50 60203150 00";
+    let (result, _) = execute(Fork::Berlin, &[(0x10, code)]);
+    assert_eq!(result.gas_used, 3 + 2600 + 2 + 3 + 100 + 2);
+    // the caller, the contract itself and the precompiles are warm:
+    // pop(balance(caller)); pop(balance(address)); pop(balance(0x09))
+    let code = "333150 303150 60093150 00";
+    let (result, _) = execute(Fork::Berlin, &[(0x10, code)]);
+    assert_eq!(result.gas_used, 3 * (2 + 100 + 2) + 1);
+    // call(gas, 0x20, 0, 0, 0, 0, 0) twice
+    let code = "6000600060006000600060205af150 6000600060006000600060205af150 00";
+    let (result, _) = execute(Fork::Berlin, &[(0x10, code)]);
+    assert_eq!(result.gas_used, 2 * (6 * 3 + 2 + 2) + 2600 + 100);
+}
+
+#[test]
+fn revert() {
+    // call(gas, 0x20, 0, 0, 0, 0, 0); sstore(0, gas cost of pop(balance(0x30)))
+    let caller = "6000600060006000600060205af150 5a60303150 5a900360005500";
+    // pop(balance(0x30)); revert(0, 0)
+    let callee = "60303150 60006000fd";
+    let (result, state) = execute(Fork::Berlin, &[(0x10, caller), (0x20, callee)]);
+    assert_eq!(result.halt, Halt::Stop);
+    // the access in the reverted frame is discarded
+    assert_eq!(state.storage(&address(0x10), &u(0)), u(3 + 2600 + 2 + 2));
+    // pop(balance(0x30)); stop
+    let callee = "60303150 00";
+    let (_, state) = execute(Fork::Berlin, &[(0x10, caller), (0x20, callee)]);
+    assert_eq!(state.storage(&address(0x10), &u(0)), u(3 + 100 + 2 + 2));
+}
+
+#[test]
+fn transaction() {
+    // pop(balance(0x20)); pop(sload(0))
+    let code = "60203150 60005450 00";
+    let mut host = Host::new(Fork::Berlin, new_state(&[(0x10, code)]));
+    let tx = Transaction {
+        sender: address(1),
+        to: Some(address(0x10)),
+        gas_limit: 100_000,
+        access_list: vec![
+            AccessListItem {
+                address: address(0x20),
+                storage_keys: Vec::new(),
+            },
+            AccessListItem {
+                address: address(0x10),
+                storage_keys: vec![H256::default()],
+            },
+        ],
+        ..Default::default()
+    };
+    let receipt = host.transact(&tx).unwrap();
+    assert!(receipt.is_success());
+    let intrinsic = 21000 + 2 * 2400 + 1900;
+    assert_eq!(receipt.gas_used, intrinsic + 2 * (3 + 100 + 2));
+}
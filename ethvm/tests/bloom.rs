@@ -0,0 +1,72 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "keccak")]
+
+extern crate ethvm;
+
+mod common;
+
+use common::{address, message, new_state};
+use ethvm::host::Host;
+use ethvm::keccak::keccak256;
+use ethvm::log::Log;
+use ethvm::schedule::Fork;
+use ethvm::types::{Bloom, H256};
+use ethvm::U256;
+
+#[test]
+fn accrue() {
+    let mut bloom = Bloom::default();
+    for i in 0..100 {
+        let data = format!("xxxxxxxxxx data {} yyyyyyyyyyyyyy", i);
+        bloom.accrue(data.as_bytes());
+        assert!(bloom.contains_input(data.as_bytes()));
+    }
+    // the same as go-ethereum
+    let expected: H256 = "c8d3ca65cdb4874300a9e39475508f23ed6da09fdbc487f89a2dcf50b09eb263"
+        .parse()
+        .unwrap();
+    assert_eq!(keccak256(bloom.as_bytes()), expected);
+    assert!(!Bloom::default().contains_input(b"test"));
+}
+
+#[test]
+fn logs() {
+    let topic = H256::from(U256::from(0xaa_u64));
+    let log = Log {
+        address: address(0x10),
+        topics: vec![topic],
+        data: vec![1, 2, 3],
+    };
+    let bloom = Bloom::from_logs(&[log]);
+    assert!(bloom.contains_input(address(0x10).as_bytes()));
+    assert!(bloom.contains_input(topic.as_bytes()));
+    assert_eq!(bloom.0.iter().map(|b| b.count_ones()).sum::<u32>(), 6);
+    let mut other = Bloom::default();
+    other.accrue(address(0x10).as_bytes());
+    other.accrue_bloom(&Bloom::from_logs(&[Log {
+        address: address(0x10),
+        topics: vec![topic],
+        data: Vec::new(),
+    }]));
+    assert_eq!(other, bloom);
+    // the bloom filter of the logs emitted by an execution:
+    // log1(0, 0, 0xaa); mstore8(0, 1); call(gas, 0x20, 0, 0, 0, 0, 0)
+    let caller = "60aa60006000a1 6001600053 6000600060006000600060205af1 00";
+    // log0(0, 0); revert(0, 0)
+    let callee = "60006000a0 60006000fd";
+    let mut host = Host::new(
+        Fork::Byzantium,
+        new_state(&[(0x10, caller), (0x20, callee)]),
+    );
+    let result = host.call(message(0, Vec::new(), 100_000));
+    // the log of the reverted frame is discarded
+    assert_eq!(result.logs.len(), 1);
+    assert_eq!(Bloom::from_logs(&result.logs), bloom);
+}
@@ -0,0 +1,116 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate ethvm;
+
+mod common;
+
+use common::{address, message, new_state, run, u};
+use ethvm::host::{ExecutionResult, Host};
+use ethvm::interpreter::Halt;
+use ethvm::schedule::Fork;
+use ethvm::state::State;
+
+/// Call the contract at `0x10` from `0x01`, with the contracts installed.
+fn execute(contracts: &[(u64, &str)], value: u64, gas: u64) -> (ExecutionResult, State) {
+    let mut state = new_state(contracts);
+    state.account_mut(&address(0x10)).balance = u(1000);
+    run(
+        Host::new(Fork::Byzantium, state),
+        message(value, Vec::new(), gas),
+    )
+}
+
+#[test]
+fn return_data() {
+    // call(gas, 0x20, 0, 0, 0, 0, 32); sstore(0, success); sstore(1, returndatasize);
+    // sstore(2, mload(0))
+    let caller = "60206000600060006000602060fff1600055 3d600155 600051600255 00";
+    // return 42
+    let callee = "602a60005260206000f3";
+    let (result, state) = execute(&[(0x10, caller), (0x20, callee)], 0, 100_000);
+    assert_eq!(result.halt, Halt::Stop);
+    assert_eq!(state.storage(&address(0x10), &u(0)), u(1));
+    assert_eq!(state.storage(&address(0x10), &u(1)), u(32));
+    assert_eq!(state.storage(&address(0x10), &u(2)), u(42));
+}
+
+#[test]
+fn revert_in_sub_call() {
+    // call(gas, 0x20, 0, 0, 0, 0, 0); sstore(0, success + 1); sstore(1, returndatasize);
+    // returndatacopy(31, 0, 1); sstore(2, mload(0))
+    let caller =
+        "6000600060006000600060205af1 600101600055 3d600155 60016000601f3e 600051600255 00";
+    // sstore(0, 1); mstore8(0, 0xbb); revert(0, 1)
+    let callee = "6001600055 60bb600053 60016000fd";
+    let (result, state) = execute(&[(0x10, caller), (0x20, callee)], 0, 100_000);
+    assert_eq!(result.halt, Halt::Stop);
+    assert_eq!(state.storage(&address(0x10), &u(0)), u(1));
+    assert_eq!(state.storage(&address(0x10), &u(1)), u(1));
+    assert_eq!(state.storage(&address(0x10), &u(2)), u(0xbb));
+    assert_eq!(state.storage(&address(0x20), &u(0)), u(0));
+    // copy the return data out of its range
+    let caller = "6000600060006000600060205af1 60026000601f3e 00";
+    let (result, _) = execute(&[(0x10, caller), (0x20, callee)], 0, 100_000);
+    assert_eq!(result.halt, Halt::ReturnDataOutOfBounds);
+}
+
+#[test]
+fn value_transfer() {
+    // call(0, 0x30, 5, 0, 0, 0, 0)
+    let caller = "6000600060006000600560306000f1 00";
+    let (result, state) = execute(&[(0x10, caller)], 0, 100_000);
+    assert_eq!(result.halt, Halt::Stop);
+    assert_eq!(state.balance(&address(0x10)), u(995));
+    assert_eq!(state.balance(&address(0x30)), u(5));
+    // 7 PUSH1s, the call, the value transfer and the new account, the unused stipend is returned
+    assert_eq!(result.gas_used, 7 * 3 + 700 + 9000 + 25000 - 2300);
+    // insufficient balance: call(0, 0x30, 5000, 0, 0, 0, 0); sstore(0, success + 1)
+    let caller = "600060006000600061138860306000f1 600101600055 00";
+    let (result, state) = execute(&[(0x10, caller)], 0, 100_000);
+    assert_eq!(result.halt, Halt::Stop);
+    assert_eq!(state.storage(&address(0x10), &u(0)), u(1));
+    assert_eq!(state.balance(&address(0x10)), u(1000));
+    assert!(!state.exists(&address(0x30)));
+}
+
+#[test]
+fn static_call() {
+    // staticcall(0xffff, 0x20, 0, 0, 0, 0); sstore(0, success + 1)
+    let caller = "6000600060006000602061fffffa 600101600055 00";
+    // sstore(0, 1)
+    let callee = "600160005500";
+    let (result, state) = execute(&[(0x10, caller), (0x20, callee)], 0, 100_000);
+    assert_eq!(result.halt, Halt::Stop);
+    assert_eq!(state.storage(&address(0x10), &u(0)), u(1));
+    assert_eq!(state.storage(&address(0x20), &u(0)), u(0));
+    assert!(result.gas_used > 0xffff);
+}
+
+#[test]
+fn delegate_call() {
+    // delegatecall(gas, 0x20, 0, 0, 0, 0)
+    let caller = "600060006000600060205af4 00";
+    // sstore(0, caller); sstore(1, callvalue)
+    let callee = "33600055 34600155 00";
+    let (result, state) = execute(&[(0x10, caller), (0x20, callee)], 7, 100_000);
+    assert_eq!(result.halt, Halt::Stop);
+    assert_eq!(state.storage(&address(0x10), &u(0)), u(1));
+    assert_eq!(state.storage(&address(0x10), &u(1)), u(7));
+    assert!(state.account(&address(0x20)).unwrap().storage.is_empty());
+}
+
+#[test]
+fn depth_limit() {
+    // sstore(0, sload(0) + 1); call(gas, address, 0, 0, 0, 0, 0)
+    let code = "600054600101600055 60006000600060006000305af1 00";
+    let (result, state) = execute(&[(0x10, code)], 0, 1_000_000_000_000_000);
+    assert_eq!(result.halt, Halt::Stop);
+    // the outermost frame and 1024 nested frames
+    assert_eq!(state.storage(&address(0x10), &u(0)), u(1025));
+}
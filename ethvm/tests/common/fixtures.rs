@@ -0,0 +1,97 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Run the JSON fixtures of <https://github.com/ethereum/tests>, each of them is a map from the
+//! names of the tests to the tests.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::{env, fmt, fs};
+
+#[derive(Default)]
+pub struct Summary {
+    pub passed: usize,
+    pub unsupported: usize,
+    pub failures: Vec<String>,
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} passed, {} failed, {} unsupported",
+            self.passed,
+            self.failures.len(),
+            self.unsupported
+        )
+    }
+}
+
+/// Collect the JSON files in a directory recursively.
+pub fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let mut entries = fs::read_dir(dir)
+        .unwrap_or_else(|err| panic!("read {}: {}", dir.display(), err))
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            files.push(path);
+        }
+    }
+}
+
+/// Run the tests in a file whose names contain the filter, `run_test` records the results of a
+/// test in the summary.
+pub fn run_file<T, F>(path: &Path, filter: Option<&str>, summary: &mut Summary, run_test: &mut F)
+where
+    T: serde::de::DeserializeOwned,
+    F: FnMut(&str, &T, &mut Summary),
+{
+    let content = fs::read_to_string(path).unwrap();
+    let tests: BTreeMap<String, T> = serde_json::from_str(&content)
+        .unwrap_or_else(|err| panic!("parse {}: {}", path.display(), err));
+    for (name, test) in &tests {
+        if filter.is_some_and(|filter| !name.contains(filter)) {
+            continue;
+        }
+        run_test(name, test, summary);
+    }
+}
+
+/// Run the tests in a directory, the filter of the names is read from the environment variable
+/// `filter_var`.
+pub fn run_dir<T, F>(dir: &Path, filter_var: &str, mut run_test: F) -> Summary
+where
+    T: serde::de::DeserializeOwned,
+    F: FnMut(&str, &T, &mut Summary),
+{
+    assert!(dir.is_dir(), "{} does not exist", dir.display());
+    let filter = env::var(filter_var).ok();
+    let mut files = Vec::new();
+    collect_files(dir, &mut files);
+    let mut summary = Summary::default();
+    for path in &files {
+        run_file(path, filter.as_deref(), &mut summary, &mut run_test);
+    }
+    summary
+}
+
+/// Assert that no test fails and at least one test passes.
+pub fn check(kind: &str, summary: &Summary) {
+    assert!(
+        summary.failures.is_empty(),
+        "{}: {}\n{}",
+        kind,
+        summary,
+        summary.failures.join("\n")
+    );
+    assert!(summary.passed > 0, "{}: {}", kind, summary);
+}
@@ -0,0 +1,63 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The helpers shared by the integration tests, which are included by `mod common;`.
+//!
+//! The tests call the contract at `0x10` from `0x01` unless they say otherwise.
+
+// Each test crate uses a part of the helpers.
+#![allow(dead_code)]
+
+pub mod fixtures;
+
+use ethvm::host::{ExecutionResult, Host, Message};
+use ethvm::state::State;
+use ethvm::types::{Address, H256};
+use ethvm::{hex, U256};
+
+/// The balance of the caller `0x01` in `new_state`.
+pub const CALLER_BALANCE: u64 = 1_000_000_000;
+
+pub fn address(v: u64) -> Address {
+    U256::from(v).into()
+}
+
+pub fn u(v: u64) -> U256 {
+    U256::from(v)
+}
+
+pub fn hash(v: u64) -> H256 {
+    U256::from(v).into()
+}
+
+/// Decode the code in hex, the spaces are ignored.
+pub fn code(code: &str) -> Vec<u8> {
+    hex::decode(&code.replace(' ', "")).unwrap()
+}
+
+/// A state with the contracts of `(address, code)` installed, and the caller `0x01` has
+/// `CALLER_BALANCE`.
+pub fn new_state(contracts: &[(u64, &str)]) -> State {
+    let mut state = State::new();
+    state.account_mut(&address(1)).balance = u(CALLER_BALANCE);
+    for (addr, contract) in contracts {
+        state.account_mut(&address(*addr)).code = code(contract);
+    }
+    state
+}
+
+/// A message call from `0x01` to `0x10`.
+pub fn message(value: u64, input: Vec<u8>, gas: u64) -> Message {
+    Message::new(address(1), address(0x10), u(value), input, gas)
+}
+
+/// Execute the message, return the result and the state after it.
+pub fn run(mut host: Host, message: Message) -> (ExecutionResult, State) {
+    let result = host.call(message);
+    (result, host.into_state())
+}
@@ -0,0 +1,200 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "keccak")]
+
+extern crate ethvm;
+
+mod common;
+
+use common::{address, code, message, new_state, run, u};
+use ethvm::host::{ExecutionResult, Host, Message};
+use ethvm::interpreter::Halt;
+use ethvm::schedule::Fork;
+use ethvm::state::State;
+use ethvm::types::{Address, H256};
+use ethvm::{hex, U256};
+
+// PUSH10 <return 42>; mstore(0, ..); return(22, 10)
+const INIT_CODE: &str = "69602a60005260206000f3600052600a6016f3";
+const RUNTIME_CODE: &str = "602a60005260206000f3";
+
+/// Call the contract at `0x10` from `0x01`.
+fn execute(fork: Fork, mut state: State, contract: &str) -> (ExecutionResult, State) {
+    state.account_mut(&address(0x10)).code = code(contract);
+    run(Host::new(fork, state), message(0, Vec::new(), 1_000_000))
+}
+
+/// Create a contract from `0x01`.
+fn deploy(fork: Fork, init_code: &str, gas: u64) -> (ExecutionResult, State) {
+    let host = Host::new(fork, new_state(&[]));
+    run(
+        host,
+        Message::create(address(1), u(0), code(init_code), gas),
+    )
+}
+
+#[test]
+fn addresses() {
+    let sender = "6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0".parse().unwrap();
+    let expected: Address = "cd234a471b72ba2f1ccf0a70fcaba648a5eecd8d".parse().unwrap();
+    assert_eq!(Address::from_create(&sender, 0), expected);
+    let expected: Address = "343c43a37d37dff08ae8c4a11544c718abb4fcf8".parse().unwrap();
+    assert_eq!(Address::from_create(&sender, 1), expected);
+    // examples in EIP-1014
+    let expected: Address = "4d1a2e2bb4f88f0250f26ffff098b0b30b26bf38".parse().unwrap();
+    assert_eq!(
+        Address::from_create2(&Address::default(), &H256::default(), &[0]),
+        expected
+    );
+    let sender = "deadbeef00000000000000000000000000000000".parse().unwrap();
+    let expected: Address = "b928f69bb1d91cd65274e3c79d8986362984fda3".parse().unwrap();
+    assert_eq!(
+        Address::from_create2(&sender, &H256::default(), &[0]),
+        expected
+    );
+}
+
+#[test]
+fn create() {
+    // mstore(0, <init code>); sstore(0, create(0, 13, 19))
+    let code = format!("72{} 600052 6013600d6000f0 600055 00", INIT_CODE);
+    let (result, state) = execute(Fork::Byzantium, new_state(&[]), &code);
+    assert_eq!(result.halt, Halt::Stop);
+    let created = Address::from_create(&address(0x10), 0);
+    assert_eq!(state.storage(&address(0x10), &u(0)), created.into());
+    assert_eq!(
+        state.code(&created),
+        &hex::decode(RUNTIME_CODE).unwrap()[..]
+    );
+    assert_eq!(state.nonce(&created), 1);
+    assert_eq!(state.nonce(&address(0x10)), 1);
+    // mstore(0, <init code>); sstore(0, create2(0, 13, 19, 0xff))
+    let code = format!("72{} 600052 60ff6013600d6000f5 600055 00", INIT_CODE);
+    let (result, state) = execute(Fork::Constantinople, new_state(&[]), &code);
+    assert_eq!(result.halt, Halt::Stop);
+    let salt = H256::from(u(0xff));
+    let created = Address::from_create2(&address(0x10), &salt, &hex::decode(INIT_CODE).unwrap());
+    assert_eq!(state.storage(&address(0x10), &u(0)), created.into());
+    assert_eq!(
+        state.code(&created),
+        &hex::decode(RUNTIME_CODE).unwrap()[..]
+    );
+    // `CREATE2` is not available before Constantinople
+    let (result, _) = execute(Fork::Byzantium, new_state(&[]), &code);
+    assert_eq!(result.halt, Halt::InvalidOpcode(0xf5));
+    // deploy directly
+    let (result, state) = deploy(Fork::Byzantium, INIT_CODE, 100_000);
+    assert_eq!(result.halt, Halt::Return);
+    let created = Address::from_create(&address(1), 0);
+    assert_eq!(result.created_address, Some(created));
+    assert_eq!(
+        state.code(&created),
+        &hex::decode(RUNTIME_CODE).unwrap()[..]
+    );
+    assert_eq!(state.nonce(&address(1)), 1);
+}
+
+#[test]
+fn collision() {
+    let mut state = new_state(&[]);
+    state
+        .account_mut(&Address::from_create(&address(0x10), 0))
+        .nonce = 1;
+    // mstore(0, <init code>); mstore(0, create(0, 13, 19) + 1); return(0, 32)
+    let code = format!(
+        "72{} 600052 6013600d6000f0 600101600052 60206000f3",
+        INIT_CODE
+    );
+    let (result, state) = execute(Fork::Byzantium, state, &code);
+    assert_eq!(result.halt, Halt::Return);
+    assert_eq!(U256::from_be_slice(&result.output[..]), u(1));
+    // the nonce is bumped and all forwarded gas is consumed
+    assert_eq!(state.nonce(&address(0x10)), 1);
+    assert!(result.gas_used > 900_000);
+}
+
+#[test]
+fn revert() {
+    // mstore8(0, 0xbb); revert(0, 1)
+    let init_code = "60bb60005360016000fd";
+    // mstore(0, <init code>); sstore(0, create(0, 22, 10) + 1); sstore(1, returndatasize)
+    let code = format!(
+        "69{} 600052 600a60166000f0 600101600055 3d600155 00",
+        init_code
+    );
+    let (result, state) = execute(Fork::Byzantium, new_state(&[]), &code);
+    assert_eq!(result.halt, Halt::Stop);
+    assert_eq!(state.storage(&address(0x10), &u(0)), u(1));
+    assert_eq!(state.storage(&address(0x10), &u(1)), u(1));
+    assert_eq!(state.nonce(&address(0x10)), 1);
+    assert!(!state.exists(&Address::from_create(&address(0x10), 0)));
+    // a successful creation returns no data
+    let code = format!("72{} 600052 6013600d6000f0 3d600155 00", INIT_CODE);
+    let (_, state) = execute(Fork::Byzantium, new_state(&[]), &code);
+    assert_eq!(state.storage(&address(0x10), &u(1)), u(0));
+}
+
+#[test]
+fn code_size_limit() {
+    // return(0, 0x6001)
+    let init_code = "61600160 00f3";
+    let (result, _) = deploy(Fork::SpuriousDragon, init_code, 10_000_000);
+    assert_eq!(result.halt, Halt::CodeSizeExceeded);
+    assert_eq!(result.gas_left, 0);
+    assert_eq!(result.created_address, None);
+    let (result, state) = deploy(Fork::TangerineWhistle, init_code, 10_000_000);
+    assert_eq!(result.halt, Halt::Return);
+    assert_eq!(state.code(&result.created_address.unwrap()).len(), 0x6001);
+    // the init code is limited since Shanghai: sstore(0, create(0, 0, size))
+    let (result, state) = execute(
+        Fork::Shanghai,
+        new_state(&[]),
+        "61c000 6000 6000 f0 600055 00",
+    );
+    assert_eq!(result.halt, Halt::Stop);
+    assert_eq!(
+        state.storage(&address(0x10), &u(0)),
+        Address::from_create(&address(0x10), 0).into()
+    );
+    let (result, _) = execute(
+        Fork::Shanghai,
+        new_state(&[]),
+        "61c001 6000 6000 f0 600055 00",
+    );
+    assert_eq!(result.halt, Halt::InitCodeSizeExceeded);
+}
+
+#[test]
+fn ef_prefix() {
+    // mstore8(0, 0xef); return(0, 1)
+    let init_code = "60ef600053 60016000f3";
+    let (result, _) = deploy(Fork::London, init_code, 100_000);
+    assert_eq!(result.halt, Halt::InvalidCodePrefix);
+    let (result, state) = deploy(Fork::Berlin, init_code, 100_000);
+    assert_eq!(result.halt, Halt::Return);
+    assert_eq!(state.code(&result.created_address.unwrap()), &[0xef][..]);
+}
+
+#[test]
+fn code_deposit_gas() {
+    // mstore8(0, 0xef); return(0, 1)
+    let init_code = "60ef600053 60016000f3";
+    let (result, state) = deploy(Fork::Homestead, init_code, 18 + 200);
+    assert_eq!(result.halt, Halt::Return);
+    assert_eq!(result.gas_left, 0);
+    assert_eq!(state.code(&result.created_address.unwrap()), &[0xef][..]);
+    let (result, state) = deploy(Fork::Homestead, init_code, 18 + 199);
+    assert_eq!(result.halt, Halt::CodeStoreOutOfGas);
+    assert!(!state.exists(&Address::from_create(&address(1), 0)));
+    // the contract is left without code before Homestead
+    let (result, state) = deploy(Fork::Frontier, init_code, 18 + 199);
+    assert_eq!(result.halt, Halt::Return);
+    assert_eq!(result.gas_used, 18);
+    assert!(state.code(&result.created_address.unwrap()).is_empty());
+}
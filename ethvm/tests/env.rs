@@ -0,0 +1,96 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate ethvm;
+extern crate serde_json;
+
+mod common;
+
+use common::{address, hash, new_state, u};
+use ethvm::env::{BlockEnv, TxEnv, BLOCK_HASH_WINDOW};
+use ethvm::host::{Host, Message};
+use ethvm::interpreter::Halt;
+use ethvm::schedule::Fork;
+use ethvm::types::H256;
+use ethvm::U256;
+
+#[test]
+fn block_hash() {
+    let mut block = BlockEnv::default();
+    for i in 0..300 {
+        block.push_block(hash(i + 1));
+    }
+    assert_eq!(block.number, 300);
+    assert_eq!(block.block_hashes.len(), BLOCK_HASH_WINDOW);
+    assert_eq!(block.block_hash(u(299)), hash(300));
+    assert_eq!(block.block_hash(u(44)), hash(45));
+    assert_eq!(block.block_hash(u(43)), H256::default());
+    assert_eq!(block.block_hash(u(300)), H256::default());
+    assert_eq!(block.block_hash(U256::MAX), H256::default());
+}
+
+#[test]
+fn serde() {
+    let json = r#"{
+        "number": "0x10",
+        "coinbase": "0x00000000000000000000000000000000000000c0",
+        "timestamp": 1000,
+        "gasLimit": "0x989680",
+        "difficulty": "0x20000",
+        "blockHashes": ["0x000000000000000000000000000000000000000000000000000000000000000f"]
+    }"#;
+    let block: BlockEnv = serde_json::from_str(json).unwrap();
+    assert_eq!(block.number, 16);
+    assert_eq!(block.coinbase, address(0xc0));
+    assert_eq!(block.timestamp, 1000);
+    assert_eq!(block.gas_limit, 10_000_000);
+    assert_eq!(block.difficulty, u(0x20000));
+    assert_eq!(block.base_fee, U256::ZERO);
+    assert_eq!(block.block_hash(u(15)), hash(15));
+    let json = serde_json::to_string(&block).unwrap();
+    assert_eq!(serde_json::from_str::<BlockEnv>(&json).unwrap(), block);
+    let tx: TxEnv = serde_json::from_str(r#"{"gasPrice": "0xa"}"#).unwrap();
+    assert_eq!(tx.gas_price, u(10));
+}
+
+#[test]
+fn opcodes() {
+    // sstore(0, origin); sstore(1, gasprice); sstore(2, coinbase); sstore(3, timestamp);
+    // sstore(4, number); sstore(5, difficulty); sstore(6, gaslimit);
+    // sstore(7, blockhash(sub(number, 1)))
+    let code = "32600055 3a600155 41600255 42600355 43600455 44600555 45600655 600143034060075500";
+    let mut host = Host::new(Fork::Byzantium, new_state(&[(0x10, code)]));
+    let mut block = BlockEnv {
+        number: 99,
+        coinbase: address(0xc0),
+        timestamp: 1234,
+        gas_limit: 8_000_000,
+        difficulty: u(131_072),
+        ..Default::default()
+    };
+    block.push_block(hash(0xbb));
+    host.set_block_env(block);
+    host.set_tx_env(TxEnv {
+        origin: address(1),
+        gas_price: u(7),
+        ..Default::default()
+    });
+    let message = Message::new(address(2), address(0x10), U256::ZERO, Vec::new(), 1_000_000);
+    let result = host.call(message);
+    assert_eq!(result.halt, Halt::Stop);
+    let state = host.state();
+    let storage = |key| state.storage(&address(0x10), &u(key));
+    assert_eq!(storage(0), u(1));
+    assert_eq!(storage(1), u(7));
+    assert_eq!(storage(2), u(0xc0));
+    assert_eq!(storage(3), u(1234));
+    assert_eq!(storage(4), u(100));
+    assert_eq!(storage(5), u(131_072));
+    assert_eq!(storage(6), u(8_000_000));
+    assert_eq!(storage(7), u(0xbb));
+}
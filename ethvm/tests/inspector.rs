@@ -0,0 +1,181 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate ethvm;
+
+mod common;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use common::{address, message, new_state, run, u};
+use ethvm::host::{ExecutionResult, Host, Message};
+use ethvm::inspector::Inspector;
+use ethvm::interpreter::{Halt, Interpreter, StackFault};
+use ethvm::log::Log;
+use ethvm::schedule::Fork;
+use ethvm::stack::{StackError, StackOp};
+use ethvm::state::State;
+use ethvm::types::Address;
+use ethvm::U256;
+
+fn execute(code: &str, inspector: Box<dyn Inspector>) -> (ExecutionResult, State) {
+    let mut state = new_state(&[(0x10, code)]);
+    state.account_mut(&address(0x10)).balance = u(7);
+    let mut host = Host::new(Fork::Byzantium, state);
+    host.set_inspector(inspector);
+    run(host, message(0, Vec::new(), 100_000))
+}
+
+#[derive(Default)]
+struct Events {
+    steps: Vec<usize>,
+    step_ends: usize,
+    calls: usize,
+    call_ends: usize,
+    logs: Vec<Log>,
+    selfdestructs: Vec<(Address, Address, U256)>,
+    stack_faults: Vec<StackFault>,
+}
+
+#[derive(Default)]
+struct Recorder(Rc<RefCell<Events>>);
+
+impl Inspector for Recorder {
+    fn step(&mut self, vm: &mut Interpreter, _host: &mut Host) -> Result<(), Halt> {
+        self.0.borrow_mut().steps.push(vm.pc());
+        Ok(())
+    }
+
+    fn step_end(&mut self, vm: &mut Interpreter, _host: &mut Host, _halt: Option<Halt>) {
+        let mut events = self.0.borrow_mut();
+        events.step_ends += 1;
+        events.stack_faults.extend(vm.stack_fault().cloned());
+    }
+
+    fn call(&mut self, _host: &mut Host, _message: &mut Message) -> Option<ExecutionResult> {
+        self.0.borrow_mut().calls += 1;
+        None
+    }
+
+    fn call_end(&mut self, _host: &mut Host, _message: &Message, _result: &mut ExecutionResult) {
+        self.0.borrow_mut().call_ends += 1;
+    }
+
+    fn log(&mut self, _vm: &Interpreter, log: &Log) {
+        self.0.borrow_mut().logs.push(log.clone());
+    }
+
+    fn selfdestruct(&mut self, address: &Address, beneficiary: &Address, value: U256) {
+        self.0
+            .borrow_mut()
+            .selfdestructs
+            .push((*address, *beneficiary, value));
+    }
+}
+
+#[test]
+fn events() {
+    let events = Rc::new(RefCell::new(Events::default()));
+    // log0(0, 0); selfdestruct(3)
+    let (result, _) = execute("60006000a06003ff", Box::new(Recorder(Rc::clone(&events))));
+    assert_eq!(result.halt, Halt::SelfDestruct);
+    let events = events.borrow();
+    assert_eq!(events.steps, vec![0, 2, 4, 5, 7]);
+    assert_eq!(events.step_ends, 5);
+    assert_eq!((events.calls, events.call_ends), (1, 1));
+    assert_eq!(events.logs.len(), 1);
+    assert_eq!(events.logs[0], result.logs[0]);
+    assert_eq!(
+        events.selfdestructs,
+        vec![(address(0x10), address(3), U256::from(7u64))]
+    );
+}
+
+#[test]
+fn stack_fault() {
+    let events = Rc::new(RefCell::new(Events::default()));
+    // 1 2 SWAP2
+    let (result, _) = execute("6001600291", Box::new(Recorder(Rc::clone(&events))));
+    assert_eq!(result.halt, Halt::StackUnderflow);
+    let events = events.borrow();
+    let fault = StackFault {
+        pc: 4,
+        opcode: 0x91,
+        error: StackError::Underflow {
+            op: StackOp::Swap(2),
+            required: 3,
+            available: 2,
+        },
+    };
+    assert_eq!(events.stack_faults, vec![fault]);
+    assert_eq!(
+        fault.to_string(),
+        "stack underflow in swap(2): 3 required, 2 available at pc 4 (SWAP2)"
+    );
+}
+
+/// Abort at the given pc, and double the value on the top of the stack before each `ADD`.
+struct Meddler(usize);
+
+impl Inspector for Meddler {
+    fn step(&mut self, vm: &mut Interpreter, _host: &mut Host) -> Result<(), Halt> {
+        if vm.pc() == self.0 {
+            return Err(Halt::Revert);
+        }
+        if vm.code()[vm.pc()] == 0x01 {
+            let top = U256::from_be_slice(vm.stack().peek().unwrap());
+            vm.stack_mut().pop().unwrap();
+            let doubled = top.wrapping_add(top).to_be_bytes();
+            vm.stack_mut().push(&doubled[..]).unwrap();
+        }
+        Ok(())
+    }
+
+    fn call_end(&mut self, _host: &mut Host, _message: &Message, result: &mut ExecutionResult) {
+        result.output.push(0xff);
+    }
+}
+
+#[test]
+fn change_execution() {
+    // sstore(0, 1 + 2); return(0, 0)
+    let code = "6001600201600055600060006000f3";
+    let (result, state) = execute(code, Box::new(Meddler(!0)));
+    assert_eq!(result.halt, Halt::Return);
+    assert_eq!(result.output, vec![0xff]);
+    assert_eq!(state.storage(&address(0x10), &U256::ZERO), U256::from(5u64));
+    // abort before SSTORE
+    let (result, state) = execute(code, Box::new(Meddler(7)));
+    assert_eq!(result.halt, Halt::Revert);
+    assert_eq!(state.storage(&address(0x10), &U256::ZERO), U256::ZERO);
+}
+
+/// Skip all calls.
+struct Skipper;
+
+impl Inspector for Skipper {
+    fn call(&mut self, _host: &mut Host, message: &mut Message) -> Option<ExecutionResult> {
+        Some(ExecutionResult {
+            halt: Halt::Stop,
+            output: vec![1, 2, 3],
+            gas_used: 0,
+            gas_left: message.gas,
+            gas_refund: 0,
+            logs: Vec::new(),
+            created_address: None,
+        })
+    }
+}
+
+#[test]
+fn skip_call() {
+    let (result, _) = execute("fe", Box::new(Skipper));
+    assert_eq!(result.halt, Halt::Stop);
+    assert_eq!(result.output, vec![1, 2, 3]);
+}
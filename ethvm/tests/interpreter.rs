@@ -0,0 +1,262 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate ethvm;
+
+mod common;
+
+use common::{address, message, new_state, run};
+use ethvm::config::VmConfig;
+use ethvm::host::{ExecutionResult, Host};
+use ethvm::interpreter::Halt;
+use ethvm::schedule::Fork;
+use ethvm::stack::{StackError, StackOp};
+use ethvm::state::State;
+use ethvm::{hex, U256};
+
+fn execute(fork: Fork, code: &str, input: &str) -> (ExecutionResult, State) {
+    let host = Host::new(fork, new_state(&[(0x10, code)]));
+    run(host, message(0, hex::decode(input).unwrap(), 100_000))
+}
+
+fn execute_with(fork: Fork, code: &str, config: VmConfig) -> ExecutionResult {
+    let mut host = Host::new(fork, new_state(&[(0x10, code)]));
+    host.set_config(config);
+    run(host, message(0, Vec::new(), 100_000)).0
+}
+
+#[test]
+fn arithmetic() {
+    // (2 + 3) * 4 - 1, then return it
+    let (result, _) = execute(
+        Fork::Byzantium,
+        "600160046003600201020360005260206000f3",
+        "",
+    );
+    assert_eq!(result.halt, Halt::Return);
+    assert_eq!(U256::from_be_slice(&result.output), U256::from(19u64));
+    // 7 PUSH1s, ADD, MUL, SUB, MSTORE and the memory expansion
+    assert_eq!(result.gas_used, 7 * 3 + 3 + 5 + 3 + 3 + 3);
+}
+
+#[test]
+fn calldata_and_storage() {
+    // sstore(0, calldataload(0)); return sload(0)
+    let (result, state) = execute(
+        Fork::Byzantium,
+        "60003560005560005460005260206000f3",
+        "000000000000000000000000000000000000000000000000000000000000002a",
+    );
+    assert_eq!(result.halt, Halt::Return);
+    assert_eq!(U256::from_be_slice(&result.output), U256::from(42u64));
+    assert_eq!(
+        state.storage(&address(0x10), &U256::ZERO),
+        U256::from(42u64)
+    );
+}
+
+#[test]
+fn jumps() {
+    // jump over an INVALID
+    let (result, _) = execute(Fork::Byzantium, "600456fe5b00", "");
+    assert_eq!(result.halt, Halt::Stop);
+    // the destination is inside the immediate value of PUSH1
+    let (result, _) = execute(Fork::Byzantium, "600456605b00", "");
+    assert_eq!(result.halt, Halt::BadJumpDestination);
+    assert_eq!(result.gas_used, 100_000);
+}
+
+#[test]
+fn halt_reasons() {
+    let (result, _) = execute(Fork::Byzantium, "01", "");
+    assert_eq!(result.halt, Halt::StackUnderflow);
+    let (result, _) = execute(Fork::Byzantium, "fe", "");
+    assert_eq!(result.halt, Halt::InvalidOpcode(0xfe));
+    let (result, _) = execute(Fork::Byzantium, "5b600056", "");
+    assert_eq!(result.halt, Halt::OutOfGas);
+    assert_eq!(result.gas_left, 0);
+}
+
+#[test]
+fn revert_discards_changes() {
+    // sstore(0, 1); log0(0, 0); revert(0, 0)
+    let code = "600160005560006000a060006000fd";
+    let (result, state) = execute(Fork::Byzantium, code, "");
+    assert_eq!(result.halt, Halt::Revert);
+    assert!(result.gas_left > 0);
+    assert!(result.logs.is_empty());
+    assert_eq!(state.storage(&address(0x10), &U256::ZERO), U256::ZERO);
+    // REVERT is not available before Byzantium
+    let (result, _) = execute(Fork::SpuriousDragon, code, "");
+    assert_eq!(result.halt, Halt::InvalidOpcode(0xfd));
+}
+
+#[test]
+fn logs() {
+    // mstore(0, 0xaa); log1(31, 1, 0xbb)
+    let (result, _) = execute(Fork::Byzantium, "60aa60005260bb6001601fa1", "");
+    assert_eq!(result.halt, Halt::Stop);
+    assert_eq!(result.logs.len(), 1);
+    assert_eq!(result.logs[0].address, address(0x10));
+    assert_eq!(U256::from(result.logs[0].topics[0]), U256::from(0xbbu64));
+    assert_eq!(result.logs[0].data, vec![0xaa]);
+}
+
+#[test]
+fn selfdestruct() {
+    let (result, state) = execute(Fork::Byzantium, "6001ff", "");
+    assert_eq!(result.halt, Halt::SelfDestruct);
+    assert_eq!(result.gas_refund, 24000);
+    assert!(!state.exists(&address(0x10)));
+}
+
+#[cfg(feature = "keccak")]
+#[test]
+fn hashes() {
+    use ethvm::keccak::{keccak256, KECCAK_EMPTY};
+    // return sha3(0, 0)
+    let (result, _) = execute(Fork::Byzantium, "600060002060005260206000f3", "");
+    assert_eq!(result.halt, Halt::Return);
+    assert_eq!(result.output, KECCAK_EMPTY.as_bytes().to_vec());
+    // 5 PUSH1s, SHA3, MSTORE and the memory expansion
+    assert_eq!(result.gas_used, 5 * 3 + 30 + 3 + 3);
+    // return sha3(0, 32) where mstore8(31, 0xff)
+    let (result, _) = execute(Fork::Byzantium, "60ff601f53602060002060005260206000f3", "");
+    let mut word = [0u8; 32];
+    word[31] = 0xff;
+    assert_eq!(result.output, keccak256(&word).as_bytes().to_vec());
+    // return extcodehash(address()) and extcodehash(0x1234)
+    let code = "30 3f 600052 611234 3f 602052 60406000f3".replace(' ', "");
+    let (result, _) = execute(Fork::Constantinople, &code, "");
+    assert_eq!(result.halt, Halt::Return);
+    let hash = keccak256(&hex::decode(&code).unwrap());
+    assert_eq!(&result.output[..32], hash.as_bytes());
+    assert_eq!(&result.output[32..], &[0u8; 32][..]);
+    assert_ne!(hash, KECCAK_EMPTY);
+    let (result, _) = execute(Fork::Byzantium, &code, "");
+    assert_eq!(result.halt, Halt::InvalidOpcode(0x3f));
+}
+
+#[test]
+fn dupn_swapn_exchange() {
+    // 1 2 3 4 DUPN(3) SWAPN(4) EXCHANGE(1, 2), then return the items from the top
+    let code = "6001600260036004 e602 e703 e801 \
+                60005260205260405260605260805260a06000f3";
+    let run = |eip663| {
+        let config = VmConfig {
+            eip663,
+            ..VmConfig::default()
+        };
+        execute_with(Fork::Cancun, code, config)
+    };
+    let result = run(false);
+    assert_eq!(result.halt, Halt::InvalidOpcode(0xe6));
+    let result = run(true);
+    assert_eq!(result.halt, Halt::Return);
+    // [1, 2, 3, 4, 2] -> [2, 2, 3, 4, 1] -> [2, 4, 3, 2, 1] from the bottom
+    let items: Vec<_> = result
+        .output
+        .chunks(32)
+        .map(|item| U256::from_be_slice(item).low_u64())
+        .collect();
+    assert_eq!(items, vec![1, 2, 3, 4, 2]);
+    // the arguments of SWAPN and EXCHANGE are beyond the configured limits
+    let config = VmConfig {
+        eip663: true,
+        dupn_swapn_limit: 3,
+        ..VmConfig::default()
+    };
+    let result = execute_with(Fork::Cancun, code, config);
+    assert_eq!(result.halt, Halt::InvalidArgument);
+    let config = VmConfig {
+        eip663: true,
+        exchange_limit: 1,
+        ..VmConfig::default()
+    };
+    let result = execute_with(Fork::Cancun, code, config);
+    assert_eq!(result.halt, Halt::InvalidArgument);
+}
+
+#[test]
+fn dupn_immediate_jumpdest() {
+    // jump to 4, which is the immediate value of DUPN if it is available
+    let run = |eip663| {
+        let config = VmConfig {
+            eip663,
+            ..VmConfig::default()
+        };
+        execute_with(Fork::Cancun, "600456e65b00", config)
+    };
+    assert_eq!(run(false).halt, Halt::Stop);
+    assert_eq!(run(true).halt, Halt::BadJumpDestination);
+}
+
+#[test]
+fn stack_error_halts() {
+    let err = StackError::InvalidArgument {
+        op: StackOp::Exchange(17, 1),
+    };
+    assert_eq!(Halt::from(err), Halt::InvalidArgument);
+    assert_eq!(
+        Halt::InvalidArgument.to_string(),
+        "invalid instruction argument"
+    );
+}
+
+#[test]
+fn config_limits() {
+    let run = |code: &str, config: VmConfig| execute_with(Fork::Byzantium, code, config);
+    // 4 PUSH1s
+    let code = "6001600160016001";
+    assert_eq!(run(code, VmConfig::default()).halt, Halt::Stop);
+    let config = VmConfig {
+        stack_limit: 3,
+        ..VmConfig::default()
+    };
+    assert_eq!(run(code, config).halt, Halt::StackOverflow);
+    // mstore(0x40, 1), which expands the memory to 0x60 bytes
+    let code = "6001604052";
+    assert_eq!(run(code, VmConfig::default()).halt, Halt::Stop);
+    let config = VmConfig {
+        memory_limit: 0x40,
+        ..VmConfig::default()
+    };
+    assert_eq!(run(code, config).halt, Halt::OutOfGas);
+    // mstore(1 << 62, 1) and log0(0, 1 << 62), the gas saturates without any limit
+    let config = VmConfig {
+        memory_limit: u64::MAX,
+        ..VmConfig::default()
+    };
+    assert_eq!(
+        run("6001 674000000000000000 52", config.clone()).halt,
+        Halt::OutOfGas
+    );
+    assert_eq!(
+        run("674000000000000000 6000 a0", config).halt,
+        Halt::OutOfGas
+    );
+    // return call(gas, 0x20, 0, 0, 0, 0, 0)
+    let code = "60006000600060006000 6020 5a f1 60005260206000f3";
+    let result = run(code, VmConfig::default());
+    assert_eq!(U256::from_be_slice(&result.output), U256::from(1u64));
+    let config = VmConfig {
+        call_depth_limit: 0,
+        ..VmConfig::default()
+    };
+    let result = run(code, config);
+    assert_eq!(U256::from_be_slice(&result.output), U256::ZERO);
+    // the gas of the call is not charged either
+    let config = VmConfig {
+        gas_free: true,
+        ..VmConfig::default()
+    };
+    let result = run(code, config);
+    assert_eq!(result.halt, Halt::Return);
+    assert_eq!(result.gas_used, 0);
+    assert_eq!(result.gas_left, 100_000);
+}
@@ -0,0 +1,50 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "keccak")]
+
+extern crate ethvm;
+
+use ethvm::keccak::{keccak256, Keccak256, KECCAK_EMPTY};
+use ethvm::types::H256;
+
+fn h(s: &str) -> H256 {
+    s.parse().unwrap()
+}
+
+#[test]
+fn known_hashes() {
+    assert_eq!(keccak256(b""), KECCAK_EMPTY);
+    assert_eq!(
+        keccak256(b""),
+        h("0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470")
+    );
+    assert_eq!(
+        keccak256(b"abc"),
+        h("0x4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45")
+    );
+    assert_eq!(
+        keccak256(b"The quick brown fox jumps over the lazy dog"),
+        h("0x4d741b6f1eb29cb2a9b9911c82f56fa8d73b04959d3d9d222895df6c0b28aa15")
+    );
+}
+
+#[test]
+fn incremental() {
+    let data: Vec<u8> = (0..1000u32).map(|i| (i * 7) as u8).collect();
+    let expected = keccak256(&data);
+    for step in [1, 7, 135, 136, 137, 500].iter() {
+        let mut hasher = Keccak256::new();
+        for chunk in data.chunks(*step) {
+            hasher.update(chunk);
+        }
+        assert_eq!(hasher.finalize(), expected);
+    }
+    // the padding fills a whole block when the input is one byte shorter than the rate
+    assert_ne!(keccak256(&data[..135]), keccak256(&data[..136]));
+}
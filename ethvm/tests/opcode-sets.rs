@@ -0,0 +1,66 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate ethvm_internals;
+
+use std::convert::TryFrom;
+
+ethvm_internals::define_opcodes!(pub mod legacy, [
+    (0x00, STOP, [], 0, 0),
+    (0x01, ADD, [], 2, 1),
+    (0x60, PUSH1, [1], 0, 1),
+]);
+
+// The same mnemonics with different values, and more opcodes.
+ethvm_internals::define_opcodes!(pub mod extended, [
+    (0x00, STOP, [], 0, 0),
+    (0x01, ADD, [], 2, 1),
+    (0x0c, DOUBLE, [], 1, 1),
+    (0x5f, PUSH1, [1], 0, 1),
+    (0xe6, DUPN, [1], 1, 2),
+]);
+
+ethvm_internals::create_action_groups!(
+    LEGACY_ACTIONS,
+    legacy::OpKind,
+    fn(x: u64) -> u64,
+    [|ADD| { x + 1 }, |PUSH1| { 0x60 }],
+    { 0 }
+);
+
+ethvm_internals::create_action_groups!(
+    EXTENDED_ACTIONS,
+    extended::OpKind,
+    fn(x: u64) -> u64,
+    [|ADD| { x + 1 }, |DOUBLE| { x * 2 }, |PUSH1| { 0x5f }],
+    { 0 }
+);
+
+#[test]
+fn independent_sets() {
+    assert_eq!(legacy::OpKind::try_from(0x60), Ok(legacy::OpKind::PUSH1));
+    assert_eq!(
+        extended::OpKind::try_from(0x5f),
+        Ok(extended::OpKind::PUSH1)
+    );
+    assert_eq!(
+        legacy::OpKind::try_from(0x0c),
+        Err(legacy::error::UnknownValue(0x0c))
+    );
+    assert_eq!(extended::OpKind::DOUBLE.mnemonic(), "DOUBLE");
+    let stmt = extended::OpCodeStmt::from_hex_str("0c5f01e60200").unwrap();
+    assert_eq!(stmt.as_slice()[1], extended::OpCode::PUSH1([0x01]));
+    assert_eq!(stmt.as_slice()[2], extended::OpCode::DUPN([0x02]));
+    assert!(legacy::OpCodeStmt::from_hex_str("0c").is_err());
+    assert_eq!(LEGACY_ACTIONS[0x01](1), 2);
+    assert_eq!(LEGACY_ACTIONS[0x60](1), 0x60);
+    assert_eq!(LEGACY_ACTIONS[0x0c](1), 0);
+    assert_eq!(EXTENDED_ACTIONS[0x0c](3), 6);
+    assert_eq!(EXTENDED_ACTIONS[0x5f](1), 0x5f);
+    assert_eq!(EXTENDED_ACTIONS[0x60](1), 0);
+}
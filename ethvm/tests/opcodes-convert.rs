@@ -8,7 +8,10 @@
 
 extern crate ethvm;
 
-use ethvm::OpCodeStmt;
+use std::convert::TryFrom;
+
+use ethvm::opcode_error::UnknownValue;
+use ethvm::{Instructions, OpCode, OpCodeStmt, OpKind};
 
 #[test]
 fn convert_between_opcodes_and_binary() {
@@ -129,3 +132,68 @@ fn convert_between_opcodes_and_binary() {
                   1809b25747c40d769653ba1c9e7b8b24958e6de325f7a582975420c3e10029";
     test(opcodes, binary);
 }
+
+#[test]
+fn opkind() {
+    assert_eq!(::std::mem::size_of::<OpKind>(), 1);
+    for value in 0..=255u8 {
+        match OpKind::try_from(value) {
+            Ok(kind) => {
+                assert_eq!(kind.value(), value);
+                assert_eq!(u8::from(kind), value);
+            }
+            Err(err) => {
+                assert_eq!(err, UnknownValue(value));
+            }
+        }
+    }
+    assert_eq!(OpKind::try_from(0x60), Ok(OpKind::PUSH1));
+    assert_eq!(OpKind::PUSH32.immediate_size(), 32);
+    assert_eq!(OpKind::ADD.immediate_size(), 0);
+    assert_eq!(
+        (OpKind::ADD.stack_removed(), OpKind::ADD.stack_placed()),
+        (2, 1)
+    );
+    assert_eq!(OpKind::SWAP2.to_string(), "SWAP2");
+    let stmt = OpCodeStmt::from_hex_str_allow_unknown("61ffff01ef").unwrap();
+    let kinds: Vec<_> = stmt.as_slice().iter().map(OpCode::kind).collect();
+    assert_eq!(kinds, vec![Some(OpKind::PUSH2), Some(OpKind::ADD), None]);
+    assert_eq!(OpKind::try_from(&stmt.as_slice()[0]), Ok(OpKind::PUSH2));
+    assert_eq!(
+        OpKind::try_from(&stmt.as_slice()[2]),
+        Err(UnknownValue(0xef))
+    );
+}
+
+#[test]
+fn instructions() {
+    // PUSH2 0xffff ADD UNKNOWN(0xef) UNKNOWN(0xe6) ADD PUSH3 0x0102 (truncated)
+    let code = ethvm::hex::decode("61ffff01efe60162 0102".replace(' ', "").as_str()).unwrap();
+    let items: Vec<_> = Instructions::new(&code[..]).collect();
+    assert_eq!(
+        items,
+        vec![
+            (0, 0x61, &[0xff, 0xff][..]),
+            (3, 0x01, &[][..]),
+            (4, 0xef, &[][..]),
+            (5, 0xe6, &[][..]),
+            (6, 0x01, &[][..]),
+            (7, 0x62, &[0x01, 0x02][..]),
+        ]
+    );
+    // DUPN 0x01 with EIP-663
+    let eof_items: Vec<_> = Instructions::with_eip663(&code[..]).collect();
+    assert_eq!(eof_items[3], (5, 0xe6, &[0x01][..]));
+    assert_eq!(eof_items[4], (7, 0x62, &[0x01, 0x02][..]));
+    assert_eq!(OpKind::try_from(0xe6), Err(UnknownValue(0xe6)));
+    assert_eq!(
+        ethvm::eof_opcodes::OpKind::try_from(0xe6),
+        Ok(ethvm::eof_opcodes::OpKind::DUPN)
+    );
+    // the same instructions as the decoded ones
+    let stmt = OpCodeStmt::from_value_slice_allow_unknown(&code[..]).unwrap();
+    let values: Vec<_> = stmt.as_slice().iter().map(OpCode::value).collect();
+    let opcodes: Vec<_> = items.iter().map(|&(_, opcode, _)| opcode).collect();
+    assert_eq!(opcodes, values);
+    assert_eq!(Instructions::new(&[]).next(), None);
+}
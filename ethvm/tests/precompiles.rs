@@ -0,0 +1,266 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate ethvm;
+
+mod common;
+
+use common::{address, code, message, new_state, u};
+use ethvm::config::VmConfig;
+use ethvm::host::{Host, Message};
+use ethvm::interpreter::Halt;
+use ethvm::precompiles::Precompiles;
+use ethvm::schedule::Fork;
+use ethvm::{hex, U256};
+
+/// Run the precompiled contract at `index` with enough gas, return the output and the gas used.
+fn run(fork: Fork, index: u64, input: &str) -> Result<(String, u64), Halt> {
+    let precompiles = Precompiles::new(fork);
+    let precompile = precompiles.get(&address(index)).unwrap();
+    let input = code(input);
+    let gas = 10_000_000;
+    precompile
+        .execute(&input, gas)
+        .map(|(output, gas_left)| (hex::encode(&output)[2..].to_owned(), gas - gas_left))
+}
+
+#[test]
+fn registry() {
+    let count = |fork| Precompiles::new(fork).len();
+    assert_eq!(count(Fork::Frontier), 4);
+    assert_eq!(count(Fork::Byzantium), 8);
+    assert_eq!(count(Fork::Istanbul), 9);
+    assert_eq!(count(Fork::Cancun), 10);
+    let precompiles = Precompiles::new(Fork::Cancun);
+    assert!(precompiles.get(&address(0)).is_none());
+    assert!(precompiles.get(&address(11)).is_none());
+    assert_eq!(
+        precompiles.get(&address(10)).unwrap().name,
+        "point_evaluation"
+    );
+    let addresses: Vec<_> = Precompiles::new(Fork::Frontier).addresses().collect();
+    assert_eq!(addresses, (1..=4).map(address).collect::<Vec<_>>());
+}
+
+#[test]
+fn hashes() {
+    assert_eq!(
+        run(Fork::Frontier, 2, ""),
+        Ok((
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_owned(),
+            60
+        ))
+    );
+    assert_eq!(
+        run(Fork::Frontier, 3, ""),
+        Ok((
+            "0000000000000000000000009c1185a5c5e9fc54612808977ee8f548b2258d31".to_owned(),
+            600
+        ))
+    );
+    assert_eq!(
+        run(Fork::Frontier, 4, "0102030405"),
+        Ok(("0102030405".to_owned(), 15 + 3))
+    );
+}
+
+#[cfg(feature = "keccak")]
+#[test]
+fn ecrecover() {
+    let hash = "38d18acb67d25c8bb9942764b62f18e17054f66a817bd4295423adf9ed98873e";
+    let v = "000000000000000000000000000000000000000000000000000000000000001b";
+    let rs = "38d18acb67d25c8bb9942764b62f18e17054f66a817bd4295423adf9ed98873e\
+              789d1dd423d25f0772d2748d60f7e4b81bb14d086eba8e8e8efb6dcff8a4ae02";
+    assert_eq!(
+        run(Fork::Frontier, 1, &format!("{}{}{}", hash, v, rs)),
+        Ok((
+            "000000000000000000000000ceaccac640adf55b2028469bd36ba501f28b699d".to_owned(),
+            3000
+        ))
+    );
+    // the recovery id is not 27 or 28
+    let v = "000000000000000000000000000000000000000000000000000000000000001d";
+    assert_eq!(
+        run(Fork::Frontier, 1, &format!("{}{}{}", hash, v, rs)),
+        Ok((String::new(), 3000))
+    );
+}
+
+#[test]
+fn modexp() {
+    // 3 ** (p - 2) % p, where p is the order of the field of secp256k1
+    let input = "
+        0000000000000000000000000000000000000000000000000000000000000001
+        0000000000000000000000000000000000000000000000000000000000000020
+        0000000000000000000000000000000000000000000000000000000000000020
+        03
+        fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e
+        fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f";
+    let input = input.replace('\n', "");
+    let output = "0000000000000000000000000000000000000000000000000000000000000001".to_owned();
+    assert_eq!(run(Fork::Byzantium, 5, &input), Ok((output.clone(), 13056)));
+    assert_eq!(run(Fork::Berlin, 5, &input), Ok((output, 1360)));
+    // the modulus is zero
+    let input = "
+        0000000000000000000000000000000000000000000000000000000000000001
+        0000000000000000000000000000000000000000000000000000000000000001
+        0000000000000000000000000000000000000000000000000000000000000002
+        0302";
+    assert_eq!(
+        run(Fork::Berlin, 5, &input.replace('\n', "")),
+        Ok(("0000".to_owned(), 200))
+    );
+    // the gas cost of huge lengths is saturated
+    let input = "
+        ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff
+        0000000000000000000000000000000000000000000000000000000000000001
+        0000000000000000000000000000000000000000000000000000000000000001";
+    assert_eq!(
+        run(Fork::Berlin, 5, &input.replace('\n', "")),
+        Err(Halt::OutOfGas)
+    );
+}
+
+#[test]
+fn modexp_gas_free() {
+    // the base is 4 GiB, which is rejected instead of allocated
+    let input = "0000000000000000000000000000000000000000000000000000000100000000\
+                 0000000000000000000000000000000000000000000000000000000000000001\
+                 0000000000000000000000000000000000000000000000000000000000000001\
+                 0203";
+    let mut host = Host::new(Fork::Berlin, new_state(&[]));
+    host.set_config(VmConfig {
+        gas_free: true,
+        ..VmConfig::default()
+    });
+    let call = Message::new(address(1), address(5), u(0), code(input), 1_000_000);
+    let result = host.call(call);
+    assert_eq!(result.halt, Halt::OutOfGas);
+    // the lengths beyond `usize`
+    let input = format!(
+        "{}{}{}",
+        "ff".repeat(32),
+        "ff".repeat(32),
+        "00".repeat(31) + "01"
+    );
+    let call = Message::new(address(1), address(5), u(0), code(&input), 1_000_000);
+    assert_eq!(host.call(call).halt, Halt::OutOfGas);
+    // a small one still works
+    let input = "0000000000000000000000000000000000000000000000000000000000000001\
+                 0000000000000000000000000000000000000000000000000000000000000001\
+                 0000000000000000000000000000000000000000000000000000000000000001\
+                 020305";
+    let call = Message::new(address(1), address(5), u(0), code(input), 1_000_000);
+    let result = host.call(call);
+    assert_eq!(result.halt, Halt::Return);
+    assert_eq!(result.output, vec![0x03]);
+    assert_eq!(result.gas_left, 1_000_000);
+}
+
+#[test]
+fn bn254() {
+    let g1 = "0000000000000000000000000000000000000000000000000000000000000001\
+              0000000000000000000000000000000000000000000000000000000000000002";
+    let double = "030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3\
+                  15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4";
+    assert_eq!(
+        run(Fork::Byzantium, 6, &format!("{}{}", g1, g1)),
+        Ok((double.to_owned(), 500))
+    );
+    assert_eq!(
+        run(Fork::Istanbul, 7, &format!("{}{:064x}", g1, 2)),
+        Ok((double.to_owned(), 6000))
+    );
+    // the point is not on the curve
+    assert_eq!(
+        run(
+            Fork::Istanbul,
+            6,
+            &format!("{}{}", g1, double.replace("c4", "c5"))
+        ),
+        Err(Halt::PrecompileFailed)
+    );
+    // e(G1, G2) * e(-G1, G2) == 1
+    let neg_g1 = "0000000000000000000000000000000000000000000000000000000000000001\
+                  30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45";
+    let g2 = "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\
+              1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed\
+              090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b\
+              12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa";
+    let one = "0000000000000000000000000000000000000000000000000000000000000001";
+    let zero = "0000000000000000000000000000000000000000000000000000000000000000";
+    assert_eq!(
+        run(Fork::Istanbul, 8, &format!("{}{}{}{}", g1, g2, neg_g1, g2)),
+        Ok((one.to_owned(), 45000 + 2 * 34000))
+    );
+    assert_eq!(
+        run(Fork::Byzantium, 8, &format!("{}{}{}{}", g1, g2, g1, g2)),
+        Ok((zero.to_owned(), 100_000 + 2 * 80000))
+    );
+    assert_eq!(run(Fork::Istanbul, 8, ""), Ok((one.to_owned(), 45000)));
+    assert_eq!(run(Fork::Istanbul, 8, g1), Err(Halt::PrecompileFailed));
+}
+
+// The test vectors in EIP-152.
+#[test]
+fn blake2f() {
+    let input = "0000000c\
+                 48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5\
+                 d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b\
+                 6162630000000000000000000000000000000000000000000000000000000000\
+                 0000000000000000000000000000000000000000000000000000000000000000\
+                 0000000000000000000000000000000000000000000000000000000000000000\
+                 0000000000000000000000000000000000000000000000000000000000000000\
+                 0300000000000000000000000000000001";
+    let output = "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+                  7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923";
+    assert_eq!(run(Fork::Istanbul, 9, input), Ok((output.to_owned(), 12)));
+    // the final block indicator flag is not 0 or 1
+    let input = format!("{}02", &input[..input.len() - 2]);
+    assert_eq!(run(Fork::Istanbul, 9, &input), Err(Halt::PrecompileFailed));
+    // the size of the input is wrong
+    assert_eq!(
+        run(Fork::Istanbul, 9, &input[2..]),
+        Err(Halt::PrecompileFailed)
+    );
+}
+
+#[cfg(feature = "kzg")]
+#[test]
+fn point_evaluation() {
+    let input = "01e798154708fe7789429634053cbf9f99b619f9f084048927333fce637f549b\
+                 564c0a11a0f704f4fc3e8acfe0f8245f0ad1347b378fbf96e206da11a5d36306\
+                 24d25032e67a7e6a4910df5834b8fe70e6bcfeeac0352434196bdf4b2485d5a1\
+                 8f59a8d2a1a625a17f3fea0fe5eb8c896db3764f3185481bc22f91b4aaffcca25f\
+                 26936857bc3a7c2539ea8ec3a952b7873033e038326e87ed3e1276fd140253fa08\
+                 e9fc25fb2d9a98527fc22a2c9612fbeafdad446cbc7bcdbdcd780af2c16a";
+    let output = "0000000000000000000000000000000000000000000000000000000000001000\
+                  73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001";
+    assert_eq!(run(Fork::Cancun, 10, input), Ok((output.to_owned(), 50000)));
+    // the versioned hash does not match the commitment
+    let input = format!("02{}", &input[2..]);
+    assert_eq!(run(Fork::Cancun, 10, &input), Err(Halt::PrecompileFailed));
+}
+
+#[test]
+fn call() {
+    // mstore(0, 42); sstore(0, staticcall(gas, 4, 0, 32, 32, 32)); sstore(1, mload(32));
+    // sstore(2, staticcall(0, 2, 0, 32, 0, 0)); sstore(3, call(gas, 5, 0, 0, 0, 0, 0))
+    let code = "602a600052 60206020602060006004 5afa 600055 602051600155 \
+                600060006020600060026000fa 600255 6000600060006000600060055af1 600355";
+    let mut host = Host::new(Fork::Byzantium, new_state(&[(0x10, code)]));
+    let result = host.call(message(0, Vec::new(), 1_000_000));
+    assert_eq!(result.halt, Halt::Stop);
+    let storage = |key: u64| host.state().storage(&address(0x10), &u(key));
+    assert_eq!(storage(0), U256::ONE);
+    assert_eq!(storage(1), U256::from(42));
+    // out of gas
+    assert_eq!(storage(2), U256::ZERO);
+    // modexp with empty input
+    assert_eq!(storage(3), U256::ONE);
+}
@@ -0,0 +1,150 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate ethvm;
+
+mod common;
+
+use common::{address, message, new_state, u};
+use ethvm::env::TxEnv;
+use ethvm::host::{ExecutionResult, Host};
+use ethvm::interpreter::Halt;
+use ethvm::schedule::Fork;
+use ethvm::transaction::AccessListItem;
+use ethvm::types::H256;
+use ethvm::U256;
+
+/// Run the code at `0x10`, the slot 0 is `original` at the start, and it is warm.
+fn execute(fork: Fork, code: &str, original: u64, gas: u64) -> ExecutionResult {
+    let mut state = new_state(&[(0x10, code)]);
+    state.set_storage(&address(0x10), U256::ZERO, u(original));
+    let mut host = Host::new(fork, state);
+    host.set_tx_env(TxEnv {
+        access_list: vec![AccessListItem {
+            address: address(0x10),
+            storage_keys: vec![H256::default()],
+        }],
+        ..Default::default()
+    });
+    host.call(message(0, Vec::new(), gas))
+}
+
+/// Check the test cases of `(code, gas used, refund, original)`.
+fn check(fork: Fork, cases: &[(&str, u64, u64, u64)]) {
+    for (code, gas_used, refund, original) in cases {
+        let result = execute(fork, code, *original, 100_000);
+        assert_eq!(result.halt, Halt::Stop, "{} {}", fork, code);
+        assert_eq!(
+            (result.gas_used, result.gas_refund),
+            (*gas_used, *refund),
+            "{} {} {}",
+            fork,
+            code,
+            original
+        );
+    }
+}
+
+#[test]
+fn legacy() {
+    let cases = [
+        ("60006000556000600055", 10012, 0, 0),
+        ("60016000556000600055", 25012, 15000, 0),
+        ("60016000556001600055", 10012, 0, 1),
+        ("60006000556001600055", 25012, 15000, 1),
+    ];
+    check(Fork::Byzantium, &cases);
+    check(Fork::Petersburg, &cases);
+}
+
+// The test cases in EIP-1283.
+#[test]
+fn eip1283() {
+    check(
+        Fork::Constantinople,
+        &[
+            ("60006000556000600055", 412, 0, 0),
+            ("60006000556001600055", 20212, 0, 0),
+            ("60016000556000600055", 20212, 19800, 0),
+            ("60016000556002600055", 20212, 0, 0),
+            ("60016000556001600055", 20212, 0, 0),
+            ("60006000556000600055", 5212, 15000, 1),
+            ("60006000556001600055", 5212, 4800, 1),
+            ("60006000556002600055", 5212, 0, 1),
+            ("60026000556000600055", 5212, 15000, 1),
+            ("60026000556003600055", 5212, 0, 1),
+            ("60026000556001600055", 5212, 4800, 1),
+            ("60026000556002600055", 5212, 0, 1),
+            ("60016000556000600055", 5212, 15000, 1),
+            ("60016000556002600055", 5212, 0, 1),
+            ("60016000556001600055", 412, 0, 1),
+            ("600160005560006000556001600055", 40218, 19800, 0),
+            ("600060005560016000556000600055", 10218, 19800, 1),
+        ],
+    );
+}
+
+// The test cases in EIP-2200.
+#[test]
+fn eip2200() {
+    check(
+        Fork::Istanbul,
+        &[
+            ("60006000556000600055", 1612, 0, 0),
+            ("60006000556001600055", 20812, 0, 0),
+            ("60016000556000600055", 20812, 19200, 0),
+            ("60016000556002600055", 20812, 0, 0),
+            ("60016000556001600055", 20812, 0, 0),
+            ("60006000556000600055", 5812, 15000, 1),
+            ("60006000556001600055", 5812, 4200, 1),
+            ("60006000556002600055", 5812, 0, 1),
+            ("60026000556000600055", 5812, 15000, 1),
+            ("60026000556003600055", 5812, 0, 1),
+            ("60026000556001600055", 5812, 4200, 1),
+            ("60026000556002600055", 5812, 0, 1),
+            ("60016000556000600055", 5812, 15000, 1),
+            ("60016000556002600055", 5812, 0, 1),
+            ("60016000556001600055", 1612, 0, 1),
+            ("600160005560006000556001600055", 40818, 19200, 0),
+            ("600060005560016000556000600055", 10818, 19200, 1),
+        ],
+    );
+    // the stipend sentry: a no-op sstore(0, 1) fails if the gas left is not more than 2300
+    let result = execute(Fork::Istanbul, "6001600055", 1, 6 + 2300);
+    assert_eq!(result.halt, Halt::OutOfGas);
+    let result = execute(Fork::Istanbul, "6001600055", 1, 6 + 2301);
+    assert_eq!(result.halt, Halt::Stop);
+    assert_eq!(result.gas_used, 6 + 800);
+}
+
+// The test cases in EIP-3529, the slot is warm.
+#[test]
+fn eip3529() {
+    check(
+        Fork::London,
+        &[
+            ("60006000556000600055", 212, 0, 0),
+            ("60006000556001600055", 20112, 0, 0),
+            ("60016000556000600055", 20112, 19900, 0),
+            ("60016000556002600055", 20112, 0, 0),
+            ("60016000556001600055", 20112, 0, 0),
+            ("60006000556000600055", 3012, 4800, 1),
+            ("60006000556001600055", 3012, 2800, 1),
+            ("60006000556002600055", 3012, 0, 1),
+            ("60026000556000600055", 3012, 4800, 1),
+            ("60026000556003600055", 3012, 0, 1),
+            ("60026000556001600055", 3012, 2800, 1),
+            ("60026000556002600055", 3012, 0, 1),
+            ("60016000556000600055", 3012, 4800, 1),
+            ("60016000556002600055", 3012, 0, 1),
+            ("60016000556001600055", 212, 0, 1),
+            ("600160005560006000556001600055", 40118, 19900, 0),
+            ("600060005560016000556000600055", 5918, 7600, 1),
+        ],
+    );
+}
@@ -7,8 +7,9 @@
 // except according to those terms.
 
 extern crate ethvm;
+extern crate serde_json;
 
-use ethvm::stack::Stack;
+use ethvm::stack::{Stack, StackError, StackOp, DUPN_SWAPN_LIMIT, EXCHANGE_LIMIT};
 
 #[test]
 fn size() {
@@ -221,3 +222,241 @@ fn swap() {
     assert_eq!(stack.size(), size);
     assert_eq!(stack.peek().unwrap(), &data[..]);
 }
+
+#[test]
+fn push_after_pop() {
+    let mut stack = Stack::default();
+    let _ = stack.push(&[0xff; 32][..]);
+    let _ = stack.pop();
+    // the short item is padded with zeros, not the bytes of the popped item
+    let _ = stack.push(&[0x01][..]);
+    let mut data = [0u8; 32];
+    data[31] = 0x01;
+    assert_eq!(stack.peek().unwrap(), &data[..]);
+}
+
+#[test]
+fn clone() {
+    let mut stack = Stack::default();
+    for i in 0u8..16 {
+        let _ = stack.push(&[i][..]);
+    }
+    let mut cloned = stack.clone();
+    assert_eq!(cloned.size(), 16);
+    for _ in 0..16 {
+        assert_eq!(cloned.pop().unwrap(), stack.pop().unwrap());
+    }
+}
+
+fn item(v: u8) -> [u8; 32] {
+    let mut data = [0u8; 32];
+    data[31] = v;
+    data
+}
+
+#[test]
+fn items() {
+    let mut stack = Stack::default();
+    assert!(stack.is_empty());
+    for i in 1u8..=3 {
+        let _ = stack.push(&[i][..]);
+    }
+    assert!(!stack.is_empty());
+    assert_eq!(stack.as_slice(), &[item(1), item(2), item(3)][..]);
+    assert_eq!(
+        stack.iter().cloned().collect::<Vec<_>>(),
+        vec![item(3), item(2), item(1)]
+    );
+    assert_eq!(
+        stack.iter_bottom_first().cloned().collect::<Vec<_>>(),
+        vec![item(1), item(2), item(3)]
+    );
+    let collected: Stack = (1u8..=3).map(item).collect();
+    assert_eq!(collected, stack);
+    let _ = stack.pop();
+    assert_ne!(collected, stack);
+    assert_eq!(format!("{:?}", stack), "[0x1, 0x2]");
+}
+
+#[test]
+fn serde() {
+    let stack: Stack = vec![item(1), [0xff; 32]].into_iter().collect();
+    let json = serde_json::to_string(&stack).unwrap();
+    assert_eq!(json, format!(r#"["0x1","0x{}"]"#, "ff".repeat(32)));
+    assert_eq!(serde_json::from_str::<Stack>(&json).unwrap(), stack);
+    assert_eq!(
+        serde_json::from_str::<Stack>("[]").unwrap(),
+        Stack::default()
+    );
+    let many = format!("[{}]", vec!["\"0x0\""; 1025].join(","));
+    let mut stack = serde_json::from_str::<Stack>(&many).unwrap();
+    assert_eq!(stack.size(), 1025);
+    assert_eq!(stack.limit(), 1025);
+    assert!(matches!(stack.push(&[1]), Err(StackError::Overflow { .. })));
+}
+
+#[test]
+fn dupn_swapn_exchange() {
+    let mut stack: Stack = (0u8..=255).rev().map(item).collect();
+    // the 256th item is the bottom one
+    let _ = stack.dupn(256);
+    assert_eq!(stack.pop().unwrap(), item(255));
+    assert!(matches!(
+        stack.dupn(257),
+        Err(StackError::InvalidArgument { .. })
+    ));
+    assert!(matches!(
+        stack.dupn(0),
+        Err(StackError::InvalidArgument { .. })
+    ));
+    assert!(matches!(
+        stack.swapn(256),
+        Err(StackError::Underflow { .. })
+    ));
+    let _ = stack.swapn(255);
+    assert_eq!(stack.peek().unwrap(), &item(255)[..]);
+    assert_eq!(stack.back(255).unwrap(), &item(0)[..]);
+    let _ = stack.swapn(255);
+    // the 2nd item and the 4th item
+    let _ = stack.exchange(1, 2);
+    assert_eq!(stack.back(1).unwrap(), &item(3)[..]);
+    assert_eq!(stack.back(3).unwrap(), &item(1)[..]);
+    assert!(matches!(
+        stack.exchange(17, 1),
+        Err(StackError::InvalidArgument { .. })
+    ));
+    assert!(matches!(
+        stack.exchange(1, 0),
+        Err(StackError::InvalidArgument { .. })
+    ));
+    let mut stack: Stack = (0u8..3).map(item).collect();
+    assert!(matches!(
+        stack.exchange(1, 2),
+        Err(StackError::Underflow { .. })
+    ));
+    assert!(stack.exchange(1, 1).is_ok());
+}
+
+#[test]
+fn dupn_swapn_exchange_limits() {
+    let mut stack: Stack = (0u8..4).map(item).collect();
+    assert_eq!(stack.dupn_swapn_limit(), DUPN_SWAPN_LIMIT);
+    assert_eq!(stack.exchange_limit(), EXCHANGE_LIMIT);
+    stack.set_dupn_swapn_limit(2);
+    stack.set_exchange_limit(1);
+    assert!(stack.dupn(2).is_ok());
+    assert!(stack.swapn(2).is_ok());
+    assert_eq!(
+        stack.dupn(3),
+        Err(StackError::InvalidArgument {
+            op: StackOp::DupN(3)
+        })
+    );
+    assert_eq!(
+        stack.swapn(3),
+        Err(StackError::InvalidArgument {
+            op: StackOp::SwapN(3)
+        })
+    );
+    assert!(stack.exchange(1, 1).is_ok());
+    assert_eq!(
+        stack.exchange(1, 2),
+        Err(StackError::InvalidArgument {
+            op: StackOp::Exchange(1, 2)
+        })
+    );
+}
+
+#[test]
+fn invalid_argument() {
+    let mut stack = Stack::default();
+    assert!(matches!(
+        stack.push(&[0u8; 33][..]),
+        Err(StackError::InvalidArgument { .. })
+    ));
+    assert!(matches!(
+        stack.dup(17),
+        Err(StackError::InvalidArgument { .. })
+    ));
+    assert!(matches!(
+        stack.swap(0),
+        Err(StackError::InvalidArgument { .. })
+    ));
+    // an underflow is reported only for valid arguments
+    assert!(matches!(stack.dup(16), Err(StackError::Underflow { .. })));
+}
+
+#[test]
+fn with_limit() {
+    let mut stack = Stack::with_limit(2);
+    assert_eq!(stack.limit(), 2);
+    assert!(stack.push(&[1]).is_ok());
+    assert!(stack.dup(1).is_ok());
+    assert!(matches!(stack.push(&[2]), Err(StackError::Overflow { .. })));
+    assert!(matches!(stack.dup(1), Err(StackError::Overflow { .. })));
+    assert!(stack.pop().is_ok());
+    assert!(stack.push(&[2]).is_ok());
+    assert_eq!(Stack::default().limit(), 1024);
+
+    let mut other = Stack::default();
+    assert!(other.push(&[1]).is_ok());
+    assert!(other.push(&[2]).is_ok());
+    assert_eq!(stack, other);
+
+    let stack = Stack::from_items_with_limit((0u8..3).map(item), 3).unwrap();
+    assert_eq!(stack.limit(), 3);
+    assert_eq!(stack.size(), 3);
+    assert_eq!(
+        Stack::from_items_with_limit((0u8..3).map(item), 2).unwrap_err(),
+        StackError::Overflow {
+            op: StackOp::FromItems(3),
+            limit: 2,
+        }
+    );
+    assert_eq!(
+        Stack::from_items_with_limit((0u8..3).map(item), 2)
+            .unwrap_err()
+            .to_string(),
+        "stack overflow in creation from 3 items: the limit is 2"
+    );
+    let stack: Stack = (0..1025).map(|_| [0; 32]).collect();
+    assert_eq!(stack.limit(), 1025);
+}
+
+#[test]
+fn error_context() {
+    let mut stack: Stack = (0u8..2).map(item).collect();
+    let err = stack.swap(3).unwrap_err();
+    assert_eq!(
+        err,
+        StackError::Underflow {
+            op: StackOp::Swap(3),
+            required: 4,
+            available: 2,
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "stack underflow in swap(3): 4 required, 2 available"
+    );
+    let err = stack.push(&[0u8; 33][..]).unwrap_err();
+    assert_eq!(err.op(), StackOp::Push(33));
+    assert_eq!(err.to_string(), "invalid argument of push of 33 bytes");
+    let mut stack = Stack::with_limit(2);
+    let _ = stack.push(&[1]);
+    let _ = stack.push(&[2]);
+    let err = stack.dup(2).unwrap_err();
+    assert_eq!(
+        err,
+        StackError::Overflow {
+            op: StackOp::Dup(2),
+            limit: 2,
+        }
+    );
+    assert_eq!(err.to_string(), "stack overflow in dup(2): the limit is 2");
+    let _ = (stack.pop(), stack.pop());
+    assert_eq!(
+        stack.pop().unwrap_err().to_string(),
+        "stack underflow in pop: 1 required, 0 available"
+    );
+}
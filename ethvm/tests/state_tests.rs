@@ -0,0 +1,255 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Run the filled state tests of Ethereum (`GeneralStateTests`).
+//!
+//! The vendored fixtures in `tests/fixtures/GeneralStateTests` are always run. The ignored test
+//! `ethereum_state_tests` runs `$ETHEREUM_TESTS/GeneralStateTests`, where `ETHEREUM_TESTS` is a
+//! checkout of <https://github.com/ethereum/tests>. Set `STATE_TESTS_FILTER` to run only the
+//! tests whose names contain it.
+//!
+//! Only the supported forks (`Fork::is_supported`) are run, the cases of the later forks are
+//! counted as unsupported.
+
+#![cfg(feature = "keccak")]
+
+extern crate ethvm;
+extern crate k256;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+
+mod common;
+
+use std::collections::BTreeMap;
+use std::env;
+use std::path::Path;
+
+use common::fixtures::{check, run_dir, Summary};
+use ethvm::env::{BlockEnv, BLOCK_HASH_WINDOW};
+use ethvm::host::Host;
+use ethvm::keccak::keccak256;
+use ethvm::log::logs_hash;
+use ethvm::schedule::{Fork, Schedule};
+use ethvm::state::State;
+use ethvm::transaction::{AccessListItem, Transaction};
+use ethvm::types::{Address, H256};
+use ethvm::{hex, U256};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StateTest {
+    env: TestEnv,
+    pre: State,
+    transaction: TestTransaction,
+    post: BTreeMap<String, Vec<PostState>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TestEnv {
+    current_coinbase: Address,
+    #[serde(default)]
+    current_difficulty: U256,
+    current_gas_limit: U256,
+    current_number: U256,
+    current_timestamp: U256,
+    current_base_fee: Option<U256>,
+    current_random: Option<H256>,
+}
+
+/// The transaction with the candidates of the data, the gas limit and the value.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TestTransaction {
+    data: Vec<String>,
+    gas_limit: Vec<U256>,
+    value: Vec<U256>,
+    gas_price: Option<U256>,
+    max_fee_per_gas: Option<U256>,
+    max_priority_fee_per_gas: Option<U256>,
+    nonce: U256,
+    secret_key: H256,
+    sender: Option<Address>,
+    to: String,
+    access_lists: Option<Vec<Option<Vec<AccessListItem>>>>,
+    blob_versioned_hashes: Option<Vec<H256>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PostState {
+    hash: H256,
+    logs: H256,
+    indexes: Indexes,
+    expect_exception: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Indexes {
+    data: usize,
+    gas: usize,
+    value: usize,
+}
+
+/// The sender is derived from the secret key if it is not given.
+fn sender(tx: &TestTransaction) -> Address {
+    if let Some(sender) = tx.sender {
+        return sender;
+    }
+    let key = k256::ecdsa::SigningKey::from_slice(tx.secret_key.as_bytes()).unwrap();
+    let point = key.verifying_key().to_encoded_point(false);
+    let hash = keccak256(&point.as_bytes()[1..]);
+    Address::from_slice(&hash.as_bytes()[12..])
+}
+
+/// The block hashes are the hashes of the decimal numbers of the blocks, as in the reference
+/// implementation of the state tests.
+fn block_env(test_env: &TestEnv, fork: Fork) -> BlockEnv {
+    let number = test_env.current_number.to_u64().unwrap();
+    let first = number.saturating_sub(BLOCK_HASH_WINDOW as u64);
+    let difficulty = match test_env.current_random {
+        Some(random) if fork >= Fork::Merge => random.into(),
+        _ => test_env.current_difficulty,
+    };
+    BlockEnv {
+        number,
+        coinbase: test_env.current_coinbase,
+        timestamp: test_env.current_timestamp.to_u64().unwrap(),
+        gas_limit: test_env.current_gas_limit.to_u64().unwrap_or(u64::MAX),
+        difficulty,
+        base_fee: test_env.current_base_fee.unwrap_or_default(),
+        block_hashes: (first..number)
+            .map(|n| keccak256(n.to_string().as_bytes()))
+            .collect(),
+    }
+}
+
+/// Build the transaction of the indexes, `None` if it is not supported.
+fn transaction(tx: &TestTransaction, indexes: &Indexes) -> Option<Transaction> {
+    if tx.blob_versioned_hashes.is_some() {
+        return None;
+    }
+    let to = if tx.to.is_empty() {
+        None
+    } else {
+        Some(tx.to.parse().unwrap())
+    };
+    let access_list = tx
+        .access_lists
+        .as_ref()
+        .and_then(|lists| lists[indexes.data].clone())
+        .unwrap_or_default();
+    let (gas_price, max_priority_fee_per_gas) = match tx.max_fee_per_gas {
+        Some(max_fee) => (max_fee, tx.max_priority_fee_per_gas),
+        None => (tx.gas_price.unwrap_or_default(), None),
+    };
+    Some(Transaction {
+        sender: sender(tx),
+        to,
+        nonce: tx.nonce.to_u64()?,
+        value: tx.value[indexes.value],
+        data: hex::decode(&tx.data[indexes.data]).unwrap(),
+        gas_limit: tx.gas_limit[indexes.gas].to_u64()?,
+        gas_price,
+        max_priority_fee_per_gas,
+        access_list,
+    })
+}
+
+/// Run a case, return why it fails.
+fn run_case(test: &StateTest, fork: Fork, post: &PostState, tx: &Transaction) -> Option<String> {
+    let block = block_env(&test.env, fork);
+    let mut host = Host::new(fork, test.pre.clone());
+    host.set_block_env(block.clone());
+    // The gas limit of the block is the gas pool of the transaction.
+    let result = if tx.gas_limit > block.gas_limit {
+        Err("gas limit reached".to_owned())
+    } else {
+        host.transact(tx).map_err(|err| err.to_string())
+    };
+    let logs = match (result, &post.expect_exception) {
+        (Ok(_), Some(expected)) => return Some(format!("expect exception {}", expected)),
+        (Err(err), None) => return Some(format!("unexpected exception: {}", err)),
+        (Ok(receipt), None) => receipt.logs,
+        (Err(_), Some(_)) => Vec::new(),
+    };
+    // The coinbase is touched even if the transaction is invalid.
+    let coinbase = block.coinbase;
+    if Schedule::new(fork).no_empty {
+        if host.state().is_dead(&coinbase) {
+            host.state_mut().remove(&coinbase);
+        }
+    } else {
+        host.state_mut().account_mut(&coinbase);
+    }
+    let logs_hash = logs_hash(&logs);
+    if logs_hash != post.logs {
+        return Some(format!(
+            "logs hash mismatch: expect {}, got {}",
+            post.logs, logs_hash
+        ));
+    }
+    let root = host.state().state_root();
+    if root != post.hash {
+        let diff = test.pre.diff(host.state());
+        return Some(format!(
+            "state root mismatch: expect {}, got {}\n{}",
+            post.hash, root, diff
+        ));
+    }
+    None
+}
+
+/// Run the cases of the supported forks in a test.
+fn run_named_test(name: &str, test: &StateTest, summary: &mut Summary) {
+    for (fork_name, posts) in &test.post {
+        let fork = match fork_name.parse::<Fork>() {
+            Ok(fork) if fork.is_supported() => fork,
+            _ => {
+                summary.unsupported += posts.len();
+                continue;
+            }
+        };
+        for (i, post) in posts.iter().enumerate() {
+            let tx = match transaction(&test.transaction, &post.indexes) {
+                Some(tx) => tx,
+                None => {
+                    summary.unsupported += 1;
+                    continue;
+                }
+            };
+            match run_case(test, fork, post, &tx) {
+                Some(reason) => summary
+                    .failures
+                    .push(format!("{} {}[{}]: {}", name, fork, i, reason)),
+                None => summary.passed += 1,
+            }
+        }
+    }
+}
+
+fn run_state_tests(dir: &Path) -> Summary {
+    run_dir(dir, "STATE_TESTS_FILTER", run_named_test)
+}
+
+#[test]
+fn vendored_state_tests() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/GeneralStateTests");
+    check("state tests", &run_state_tests(&dir));
+}
+
+#[test]
+#[ignore]
+fn ethereum_state_tests() {
+    let dir = env::var_os("ETHEREUM_TESTS").expect("ETHEREUM_TESTS is not set");
+    check(
+        "state tests",
+        &run_state_tests(&Path::new(&dir).join("GeneralStateTests")),
+    );
+}
@@ -0,0 +1,107 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate ethvm;
+extern crate serde_json;
+
+mod common;
+
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+
+use common::{message, new_state};
+use ethvm::host::Host;
+use ethvm::schedule::Fork;
+use ethvm::tracer::JsonTracer;
+
+/// A writer which could be read after it is moved into the host.
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn trace(code: &str, gas: u64) -> Vec<serde_json::Value> {
+    let mut host = Host::new(Fork::Byzantium, new_state(&[(0x10, code)]));
+    let buffer = SharedBuffer::default();
+    host.set_tracer(Box::new(JsonTracer::new(buffer.clone())));
+    host.call(message(0, Vec::new(), gas));
+    let output = String::from_utf8(buffer.0.borrow().clone()).unwrap();
+    output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn steps() {
+    // mstore(0, 1 + 2)
+    let lines = trace("600160020160005200", 100_000);
+    assert_eq!(lines.len(), 7);
+    let add = &lines[2];
+    assert_eq!(add["pc"], 4);
+    assert_eq!(add["op"], 0x01);
+    assert_eq!(add["opName"], "ADD");
+    assert_eq!(add["gas"], "0x1869a");
+    assert_eq!(add["gasCost"], "0x3");
+    assert_eq!(add["stack"], serde_json::json!(["0x1", "0x2"]));
+    assert_eq!(add["depth"], 1);
+    assert_eq!(add["refund"], 0);
+    let mstore = &lines[4];
+    assert_eq!(mstore["memSize"], 0);
+    assert_eq!(mstore["gasCost"], "0x6");
+    assert_eq!(lines[5]["memSize"], 32);
+    assert_eq!(lines[5]["opName"], "STOP");
+    let summary = &lines[6];
+    assert_eq!(summary["gasUsed"], "0x12");
+    assert_eq!(summary["pass"], true);
+}
+
+#[test]
+fn errors() {
+    let lines = trace("6001600201", 5);
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[1]["gasCost"], "0x2");
+    assert_eq!(lines[1]["error"], "out of gas");
+    assert!(lines[0].get("error").is_none());
+    assert_eq!(lines[2]["pass"], false);
+    assert_eq!(lines[2]["error"], "out of gas");
+    // running out of the code is traced as STOP
+    let lines = trace("6001", 100);
+    assert_eq!(lines[1]["pc"], 2);
+    assert_eq!(lines[1]["opName"], "STOP");
+}
+
+#[test]
+fn return_data() {
+    // call(gas, 4, 0, 0, 32, 0, 0) to the identity precompile, then pop the result
+    let lines = trace("600060006020600060006004 5a f1 50 00", 100_000);
+    let pop = lines.iter().find(|line| line["opName"] == "POP").unwrap();
+    assert_eq!(pop["returnData"], format!("0x{}", "00".repeat(32)));
+    assert_eq!(lines[0]["returnData"], "0x");
+}
+
+#[cfg(feature = "keccak")]
+#[test]
+fn state_root() {
+    use common::{address, u};
+    // sstore(0, 1)
+    let lines = trace("600160005500", 100_000);
+    let mut state = new_state(&[(0x10, "600160005500")]);
+    state.set_storage(&address(0x10), u(0), u(1));
+    let summary = lines.last().unwrap();
+    assert_eq!(summary["stateRoot"], state.state_root().to_string());
+}
@@ -0,0 +1,191 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate ethvm;
+
+mod common;
+
+use common::{address, new_state, u, CALLER_BALANCE};
+use ethvm::env::BlockEnv;
+use ethvm::host::Host;
+use ethvm::interpreter::Halt;
+use ethvm::schedule::{Fork, Schedule};
+use ethvm::transaction::{Transaction, TransactionError};
+use ethvm::U256;
+
+fn block_env(base_fee: u64) -> BlockEnv {
+    BlockEnv {
+        coinbase: address(0xc0),
+        base_fee: u(base_fee),
+        ..Default::default()
+    }
+}
+
+fn new_host(fork: Fork, code: &str) -> Host {
+    let mut host = Host::new(fork, new_state(&[(0x10, code)]));
+    host.set_block_env(block_env(0));
+    host
+}
+
+fn transaction(to: Option<u64>, gas_limit: u64) -> Transaction {
+    Transaction {
+        sender: address(1),
+        to: to.map(address),
+        gas_limit,
+        gas_price: u(10),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn transfer() {
+    let mut host = new_host(Fork::Berlin, "");
+    let mut tx = transaction(Some(0x20), 30_000);
+    tx.value = u(1000);
+    let receipt = host.transact(&tx).unwrap();
+    assert!(receipt.is_success());
+    assert_eq!(receipt.gas_used, 21000);
+    let state = host.state();
+    assert_eq!(
+        state.balance(&address(1)),
+        u(CALLER_BALANCE - 1000 - 210_000)
+    );
+    assert_eq!(state.balance(&address(0x20)), u(1000));
+    assert_eq!(state.balance(&address(0xc0)), u(210_000));
+    assert_eq!(state.nonce(&address(1)), 1);
+}
+
+#[test]
+fn dynamic_fee() {
+    let mut host = new_host(Fork::London, "");
+    host.set_block_env(block_env(7));
+    let mut tx = transaction(Some(0x20), 21000);
+    tx.max_priority_fee_per_gas = Some(u(2));
+    let receipt = host.transact(&tx).unwrap();
+    assert!(receipt.is_success());
+    // the base fee is burnt, the coinbase gets the priority fee
+    let state = host.state();
+    assert_eq!(state.balance(&address(1)), u(CALLER_BALANCE - 9 * 21000));
+    assert_eq!(state.balance(&address(0xc0)), u(2 * 21000));
+    // the fee cap is less than the base fee
+    let mut host = new_host(Fork::London, "");
+    host.set_block_env(block_env(11));
+    let err = host.transact(&tx).unwrap_err();
+    assert_eq!(err, TransactionError::FeeCapTooLow);
+    // not supported before London
+    let mut host = new_host(Fork::Berlin, "");
+    let err = host.transact(&tx).unwrap_err();
+    assert_eq!(err, TransactionError::UnsupportedType);
+}
+
+#[test]
+fn intrinsic_gas() {
+    let mut tx = transaction(Some(0x20), 100_000);
+    tx.data = vec![0, 1];
+    assert_eq!(
+        tx.intrinsic_gas(&Schedule::new(Fork::Byzantium)),
+        21000 + 4 + 68
+    );
+    assert_eq!(
+        tx.intrinsic_gas(&Schedule::new(Fork::Istanbul)),
+        21000 + 4 + 16
+    );
+    let mut tx = transaction(None, 100_000);
+    tx.data = vec![1; 33];
+    assert_eq!(
+        tx.intrinsic_gas(&Schedule::new(Fork::Frontier)),
+        21000 + 33 * 68
+    );
+    assert_eq!(
+        tx.intrinsic_gas(&Schedule::new(Fork::Shanghai)),
+        53000 + 33 * 16 + 2 * 2
+    );
+}
+
+#[test]
+fn invalid() {
+    let mut host = new_host(Fork::Berlin, "");
+    let mut tx = transaction(Some(0x20), 21000);
+    tx.nonce = 1;
+    let err = host.transact(&tx).unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::NonceMismatch {
+            expected: 0,
+            got: 1
+        }
+    );
+    let mut tx = transaction(Some(0x20), 20999);
+    let err = host.transact(&tx).unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::IntrinsicGasTooLow {
+            intrinsic: 21000,
+            limit: 20999
+        }
+    );
+    tx.gas_limit = 100_000_001;
+    let err = host.transact(&tx).unwrap_err();
+    assert_eq!(err, TransactionError::InsufficientFunds);
+    assert_eq!(host.state().nonce(&address(1)), 0);
+    assert_eq!(host.state().balance(&address(1)), u(CALLER_BALANCE));
+    let mut host = new_host(Fork::Berlin, "00");
+    let mut tx = transaction(Some(0x20), 21000);
+    tx.sender = address(0x10);
+    let err = host.transact(&tx).unwrap_err();
+    assert_eq!(err, TransactionError::SenderNotEoa);
+}
+
+#[test]
+fn refund() {
+    // sstore(0, 0)
+    let mut host = new_host(Fork::Byzantium, "6000600055");
+    host.state_mut().set_storage(&address(0x10), u(0), u(1));
+    let receipt = host.transact(&transaction(Some(0x10), 100_000)).unwrap();
+    assert!(receipt.is_success());
+    // the refund is capped by half of the gas used
+    let gas_used = 21000 + 3 + 3 + 5000;
+    assert_eq!(receipt.gas_refunded, gas_used / 2);
+    assert_eq!(receipt.gas_used, gas_used - gas_used / 2);
+    assert_eq!(
+        host.state().balance(&address(0xc0)),
+        u(receipt.gas_used * 10)
+    );
+}
+
+#[test]
+fn failure() {
+    let mut host = new_host(Fork::Byzantium, "fe");
+    let mut tx = transaction(Some(0x10), 100_000);
+    tx.value = u(1000);
+    let receipt = host.transact(&tx).unwrap();
+    assert_eq!(receipt.halt, Halt::InvalidOpcode(0xfe));
+    assert_eq!(receipt.gas_used, 100_000);
+    let state = host.state();
+    assert_eq!(state.balance(&address(1)), u(CALLER_BALANCE - 1_000_000));
+    assert_eq!(state.balance(&address(0x10)), U256::ZERO);
+    assert_eq!(state.nonce(&address(1)), 1);
+}
+
+#[cfg(feature = "keccak")]
+#[test]
+fn create() {
+    use ethvm::hex;
+    use ethvm::types::Address;
+
+    let mut host = new_host(Fork::Byzantium, "");
+    let mut tx = transaction(None, 100_000);
+    // return 42
+    tx.data = hex::decode("602a60005260206000f3").unwrap();
+    let receipt = host.transact(&tx).unwrap();
+    assert!(receipt.is_success());
+    let created = Address::from_create(&address(1), 0);
+    assert_eq!(receipt.contract_address, Some(created));
+    assert_eq!(host.state().nonce(&address(1)), 1);
+    assert_eq!(host.state().code(&created).len(), 32);
+}
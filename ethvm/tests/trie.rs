@@ -0,0 +1,215 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "keccak")]
+
+extern crate ethvm;
+
+use ethvm::keccak::keccak256;
+use ethvm::log::{logs_hash, Log};
+use ethvm::rlp::{self, DecoderError, Rlp};
+use ethvm::state::{Account, State};
+use ethvm::trie::{trie_root, Trie, EMPTY_ROOT};
+use ethvm::types::{Address, H256};
+use ethvm::U256;
+
+fn h(s: &str) -> H256 {
+    s.parse().unwrap()
+}
+
+#[test]
+fn encode() {
+    assert_eq!(rlp::encode(&b"dog"[..]), b"\x83dog".to_vec());
+    assert_eq!(rlp::encode(&b""[..]), vec![0x80]);
+    assert_eq!(rlp::encode(&vec![0x0fu8]), vec![0x0f]);
+    assert_eq!(rlp::encode(&0u64), vec![0x80]);
+    assert_eq!(rlp::encode(&1024u64), vec![0x82, 0x04, 0x00]);
+    assert_eq!(rlp::encode(&U256::from(0x7f)), vec![0x7f]);
+    assert_eq!(
+        rlp::encode_list(&[&b"cat"[..], &b"dog"[..]]),
+        b"\xc8\x83cat\x83dog".to_vec()
+    );
+    let long = vec![b'a'; 56];
+    let mut expected = vec![0xb8, 56];
+    expected.extend_from_slice(&long);
+    assert_eq!(rlp::encode(&long), expected);
+    assert_eq!(rlp::encode_list::<Vec<u8>>(&[]), vec![0xc0]);
+}
+
+#[test]
+fn decode() {
+    assert_eq!(rlp::decode::<Vec<u8>>(b"\x83dog"), Ok(b"dog".to_vec()));
+    assert_eq!(rlp::decode::<Vec<u8>>(&[0x0f]), Ok(vec![0x0f]));
+    assert_eq!(rlp::decode::<u64>(&[0x80]), Ok(0));
+    assert_eq!(rlp::decode::<u64>(&[0x82, 0x04, 0x00]), Ok(1024));
+    assert_eq!(rlp::decode::<U256>(&[0x7f]), Ok(U256::from(0x7f)));
+    assert_eq!(
+        rlp::decode_list::<Vec<u8>>(b"\xc8\x83cat\x83dog"),
+        Ok(vec![b"cat".to_vec(), b"dog".to_vec()])
+    );
+    let long = vec![b'a'; 56];
+    assert_eq!(rlp::decode::<Vec<u8>>(&rlp::encode(&long)), Ok(long));
+    // [[], [[]], [[], [[]]]]
+    let items = Rlp::new(&[0xc7, 0xc0, 0xc1, 0xc0, 0xc3, 0xc0, 0xc1, 0xc0])
+        .unwrap()
+        .items()
+        .unwrap();
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[2].items().unwrap()[1].items().unwrap().len(), 1);
+    let log = Log {
+        address: Address::from_slice(&[0x11; 20]),
+        topics: vec![H256::from_slice(&[0x22; 32])],
+        data: vec![1, 2, 3],
+    };
+    assert_eq!(rlp::decode::<Log>(&rlp::encode(&log)), Ok(log));
+}
+
+#[test]
+fn decode_errors() {
+    assert_eq!(rlp::decode::<Vec<u8>>(&[]), Err(DecoderError::TooShort));
+    assert_eq!(
+        rlp::decode::<Vec<u8>>(b"\x83do"),
+        Err(DecoderError::TooShort)
+    );
+    assert_eq!(
+        rlp::decode::<Vec<u8>>(b"\x83dogs"),
+        Err(DecoderError::TrailingBytes)
+    );
+    // a single byte less than 0x80 with a header
+    assert_eq!(
+        rlp::decode::<Vec<u8>>(&[0x81, 0x0f]),
+        Err(DecoderError::NonCanonical)
+    );
+    // a short string with a long header
+    let mut bytes = vec![0xb8, 55];
+    bytes.extend_from_slice(&[b'a'; 55]);
+    assert_eq!(
+        rlp::decode::<Vec<u8>>(&bytes),
+        Err(DecoderError::NonCanonical)
+    );
+    // an integer with leading zeros
+    assert_eq!(
+        rlp::decode::<u64>(&[0x82, 0x00, 0x01]),
+        Err(DecoderError::NonCanonical)
+    );
+    assert_eq!(
+        rlp::decode::<u64>(&rlp::encode(&U256::MAX)),
+        Err(DecoderError::BadSize)
+    );
+    assert_eq!(rlp::decode::<H256>(b"\x83dog"), Err(DecoderError::BadSize));
+    assert_eq!(
+        rlp::decode::<u64>(&[0xc0]),
+        Err(DecoderError::ExpectedBytes)
+    );
+    assert_eq!(
+        rlp::decode_list::<u64>(&[0x80]),
+        Err(DecoderError::ExpectedList)
+    );
+}
+
+// The test vectors in the trie tests of Ethereum.
+#[test]
+fn root() {
+    assert_eq!(trie_root(Vec::<(Vec<u8>, Vec<u8>)>::new()), EMPTY_ROOT);
+    assert_eq!(keccak256(&[0x80]), EMPTY_ROOT);
+    assert_eq!(
+        trie_root(vec![
+            ("do", "verb"),
+            ("horse", "stallion"),
+            ("doge", "coin"),
+            ("dog", "puppy"),
+        ]),
+        h("0x5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84")
+    );
+    assert_eq!(
+        trie_root(vec![
+            ("doe", "reindeer"),
+            ("dog", "puppy"),
+            ("dogglesworth", "cat"),
+        ]),
+        h("0x8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3")
+    );
+    // the last value of a key is taken
+    assert_eq!(
+        trie_root(vec![
+            ("A", "a"),
+            ("A", "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa")
+        ]),
+        trie_root(vec![(
+            "A",
+            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+        )])
+    );
+}
+
+#[test]
+fn trie() {
+    let mut trie = Trie::new();
+    assert!(trie.is_empty());
+    assert_eq!(trie.root(), EMPTY_ROOT);
+    let items = [
+        ("do", "verb"),
+        ("horse", "stallion"),
+        ("doge", "coin"),
+        ("dog", "puppy"),
+    ];
+    for &(key, value) in &items {
+        assert_eq!(trie.insert(key.as_bytes(), value.as_bytes().to_vec()), None);
+    }
+    assert_eq!(
+        trie.root(),
+        h("0x5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84")
+    );
+    for &(key, value) in &items {
+        assert_eq!(trie.get(key.as_bytes()), Some(value.as_bytes()));
+    }
+    assert_eq!(trie.get(b"d"), None);
+    assert_eq!(trie.get(b"dogs"), None);
+    assert_eq!(
+        trie.insert(b"dog", b"hound".to_vec()),
+        Some(b"puppy".to_vec())
+    );
+    assert_eq!(trie.get(b"dog"), Some(&b"hound"[..]));
+    // removals restore the root of the rest
+    let root = trie.root();
+    trie.insert(b"dogglesworth", b"cat".to_vec());
+    trie.insert(b"horses", b"herd".to_vec());
+    assert_ne!(trie.root(), root);
+    assert_eq!(trie.remove(b"horses"), Some(b"herd".to_vec()));
+    assert_eq!(trie.remove(b"dogglesworth"), Some(b"cat".to_vec()));
+    assert_eq!(trie.remove(b"dogglesworth"), None);
+    assert_eq!(trie.root(), root);
+    // an empty value removes the key
+    assert_eq!(trie.insert(b"do", Vec::new()), Some(b"verb".to_vec()));
+    assert_eq!(
+        trie.root(),
+        trie_root(vec![
+            ("horse", "stallion"),
+            ("doge", "coin"),
+            ("dog", "hound")
+        ])
+    );
+    for key in &["horse", "doge", "dog"] {
+        trie.remove(key.as_bytes());
+    }
+    assert!(trie.is_empty());
+}
+
+#[test]
+fn state_root() {
+    assert_eq!(State::new().state_root(), EMPTY_ROOT);
+    assert_eq!(Account::default().storage_root(), EMPTY_ROOT);
+    // an empty account is still in the trie
+    let mut state = State::new();
+    state.account_mut(&Default::default());
+    assert_ne!(state.state_root(), EMPTY_ROOT);
+    assert_eq!(
+        logs_hash(&[]),
+        h("0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347")
+    );
+}
@@ -0,0 +1,160 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate ethvm;
+
+use ethvm::U256;
+
+fn u(s: &str) -> U256 {
+    s.parse().unwrap()
+}
+
+fn from_u128(v: u128) -> U256 {
+    U256([v as u64, (v >> 64) as u64, 0, 0])
+}
+
+#[test]
+fn bytes() {
+    let mut bytes = [0u8; 32];
+    for (i, b) in bytes.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    let v = U256::from_be_bytes(bytes);
+    assert_eq!(v.to_be_bytes(), bytes);
+    assert_eq!(v.byte(0), 0);
+    assert_eq!(v.byte(31), 31);
+    assert_eq!(v.byte(32), 0);
+    assert_eq!(U256::from_be_slice(&[1, 2]), U256::from(0x0102u64));
+}
+
+#[test]
+fn parse_and_display() {
+    assert_eq!(u("0"), U256::ZERO);
+    assert_eq!(u("0x0"), U256::ZERO);
+    assert_eq!(u("255"), U256::from(255u64));
+    assert_eq!(u("0xff"), U256::from(255u64));
+    let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+    assert_eq!(u(max), U256::MAX);
+    assert_eq!(format!("{}", U256::MAX), max);
+    assert!(
+        "115792089237316195423570985008687907853269984665640564039457584007913129639936"
+            .parse::<U256>()
+            .is_err()
+    );
+    assert_eq!(format!("{:#x}", U256::from(0x1234u64)), "0x1234");
+    assert_eq!(format!("{:x}", U256::ZERO), "0");
+    assert_eq!(
+        format!("{}", u("0x10000000000000000")),
+        "18446744073709551616"
+    );
+}
+
+#[test]
+fn arithmetic_against_u128() {
+    let samples = [
+        0u128,
+        1,
+        2,
+        3,
+        7,
+        0xff,
+        0xffff_ffff,
+        0x1_0000_0000,
+        0xffff_ffff_ffff_ffff,
+        0x1_0000_0000_0000_0000,
+        0x1234_5678_9abc_def0_0fed_cba9_8765_4321,
+        0xffff_ffff_ffff_ffff_ffff_ffff_ffff_ffff,
+    ];
+    for a in samples.iter() {
+        for b in samples.iter() {
+            let (x, y) = (from_u128(*a), from_u128(*b));
+            if let Some(v) = a.checked_add(*b) {
+                assert_eq!(x.wrapping_add(y), from_u128(v));
+            }
+            if let Some(v) = a.checked_sub(*b) {
+                assert_eq!(x.wrapping_sub(y), from_u128(v));
+            }
+            if let Some(v) = a.checked_mul(*b) {
+                assert_eq!(x.wrapping_mul(y), from_u128(v));
+            }
+            if *b != 0 {
+                assert_eq!(x.evm_div(y), from_u128(a / b));
+                assert_eq!(x.evm_rem(y), from_u128(a % b));
+            }
+            assert_eq!(x < y, a < b);
+        }
+    }
+}
+
+#[test]
+fn division() {
+    let a = u("0xfedcba9876543210fedcba9876543210fedcba9876543210fedcba9876543210");
+    let b = u("0x123456789abcdef0123456789abcdef");
+    let (q, r) = a.div_rem(b);
+    assert!(r < b);
+    assert_eq!(q.wrapping_mul(b).wrapping_add(r), a);
+    assert_eq!(U256::MAX.evm_div(U256::MAX), U256::ONE);
+    assert_eq!(U256::MAX.evm_rem(U256::MAX), U256::ZERO);
+    assert_eq!(a.evm_div(U256::ZERO), U256::ZERO);
+    assert_eq!(a.evm_rem(U256::ZERO), U256::ZERO);
+}
+
+#[test]
+fn signed() {
+    let minus_one = U256::MAX;
+    let minus_two = U256::from(2u64).wrapping_neg();
+    let min = U256::ONE << 255;
+    assert_eq!(U256::from(4u64).signed_div(minus_two), minus_two);
+    assert_eq!(min.signed_div(minus_one), min);
+    assert_eq!(minus_one.signed_rem(U256::from(2u64)), minus_one);
+    assert_eq!(
+        U256::from(10u64)
+            .wrapping_neg()
+            .signed_rem(U256::from(3u64)),
+        minus_one
+    );
+    assert_eq!(minus_one.signed_cmp(&U256::ONE), ::std::cmp::Ordering::Less);
+    assert_eq!(U256::from(0xffu64).sign_extend(U256::ZERO), minus_one);
+    assert_eq!(
+        U256::from(0x7fu64).sign_extend(U256::ZERO),
+        U256::from(0x7fu64)
+    );
+    assert_eq!(minus_one.sign_extend(U256::from(31u64)), minus_one);
+}
+
+#[test]
+fn modular() {
+    assert_eq!(
+        U256::MAX.add_mod(U256::from(2u64), U256::from(2u64)),
+        U256::ONE
+    );
+    assert_eq!(
+        U256::MAX.mul_mod(U256::MAX, U256::from(12u64)),
+        U256::from(9u64)
+    );
+    assert_eq!(U256::MAX.mul_mod(U256::MAX, U256::ZERO), U256::ZERO);
+    assert_eq!(
+        U256::from(2u64).wrapping_pow(U256::from(255u64)),
+        U256::ONE << 255
+    );
+    assert_eq!(
+        U256::from(2u64).wrapping_pow(U256::from(256u64)),
+        U256::ZERO
+    );
+    assert_eq!(U256::from(3u64).wrapping_pow(U256::ZERO), U256::ONE);
+}
+
+#[test]
+fn shift() {
+    let v = u("0x8000000000000000000000000000000000000000000000000000000000000001");
+    assert_eq!(v << 1, U256::from(2u64));
+    assert_eq!(v >> 255, U256::ONE);
+    assert_eq!(v >> 256, U256::ZERO);
+    assert_eq!(U256::ONE << 64, U256([0, 1, 0, 0]));
+    assert_eq!(U256([0, 1, 0, 0]) >> 1, U256([1 << 63, 0, 0, 0]));
+}
@@ -0,0 +1,207 @@
+// Copyright (C) 2018 Boyu Yang
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Run the legacy VM tests of Ethereum (`VMTests`), each of them executes a single frame.
+//!
+//! The vendored fixtures in `tests/fixtures/VMTests` are always run. The ignored test
+//! `ethereum_vm_tests` runs `$ETHEREUM_TESTS/LegacyTests/Constantinople/VMTests`, where
+//! `ETHEREUM_TESTS` is a checkout of <https://github.com/ethereum/tests>. Set `VM_TESTS_FILTER`
+//! to run only the tests whose names contain it.
+//!
+//! As in the reference implementation, the tests run with the Frontier rules, the value of the
+//! frame is not transferred, and only the storage of the accounts in `post` is compared.
+
+#![cfg(feature = "keccak")]
+
+extern crate ethvm;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+
+mod common;
+
+use std::collections::BTreeMap;
+use std::env;
+use std::path::Path;
+
+use common::fixtures::{check, run_dir, Summary};
+use ethvm::env::{BlockEnv, TxEnv, BLOCK_HASH_WINDOW};
+use ethvm::host::{CallKind, Host, Message};
+use ethvm::keccak::keccak256;
+use ethvm::log::logs_hash;
+use ethvm::schedule::Fork;
+use ethvm::state::State;
+use ethvm::types::{Address, H256};
+use ethvm::{hex, U256};
+
+#[derive(Deserialize)]
+struct VmTest {
+    env: TestEnv,
+    exec: Exec,
+    pre: State,
+    /// The gas left, absent if the execution fails.
+    gas: Option<U256>,
+    logs: Option<H256>,
+    out: Option<String>,
+    /// The post state, absent if the execution fails.
+    post: Option<State>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TestEnv {
+    current_coinbase: Address,
+    current_difficulty: U256,
+    current_gas_limit: U256,
+    current_number: U256,
+    current_timestamp: U256,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Exec {
+    address: Address,
+    caller: Address,
+    code: String,
+    data: String,
+    gas: U256,
+    gas_price: U256,
+    origin: Address,
+    value: U256,
+}
+
+/// The block hashes are the hashes of the decimal numbers of the blocks, as in the reference
+/// implementation of the VM tests.
+fn block_env(test_env: &TestEnv) -> BlockEnv {
+    let number = test_env.current_number.to_u64().unwrap();
+    let first = number.saturating_sub(BLOCK_HASH_WINDOW as u64);
+    BlockEnv {
+        number,
+        coinbase: test_env.current_coinbase,
+        timestamp: test_env.current_timestamp.to_u64().unwrap(),
+        gas_limit: test_env.current_gas_limit.to_u64().unwrap_or(u64::MAX),
+        difficulty: test_env.current_difficulty,
+        base_fee: U256::ZERO,
+        block_hashes: (first..number)
+            .map(|n| keccak256(n.to_string().as_bytes()))
+            .collect(),
+    }
+}
+
+/// The non-zero storage slots of an account.
+fn storage(state: &State, address: &Address) -> BTreeMap<U256, U256> {
+    state
+        .account(address)
+        .map(|account| {
+            account
+                .storage
+                .iter()
+                .filter(|(_, value)| !value.is_zero())
+                .map(|(key, value)| (*key, *value))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Run a test, return why it fails.
+fn run_test(test: &VmTest) -> Option<String> {
+    let exec = &test.exec;
+    let mut state = test.pre.clone();
+    state.account_mut(&exec.address).code = hex::decode(&exec.code).unwrap();
+    let mut host = Host::new(Fork::Frontier, state);
+    host.set_block_env(block_env(&test.env));
+    host.set_tx_env(TxEnv {
+        origin: exec.origin,
+        gas_price: exec.gas_price,
+        access_list: Vec::new(),
+    });
+    // `CALLVALUE` is the value, but it is not transferred, as a `DELEGATECALL`.
+    let message = Message {
+        kind: CallKind::DelegateCall,
+        ..Message::new(
+            exec.caller,
+            exec.address,
+            exec.value,
+            hex::decode(&exec.data).unwrap(),
+            exec.gas.to_u64().unwrap(),
+        )
+    };
+    let result = host.call(message);
+    let post = match test.post {
+        Some(ref post) => post,
+        None if result.halt.is_success() => return Some("expect failure".to_owned()),
+        None => return None,
+    };
+    if !result.halt.is_success() {
+        return Some(format!("unexpected halt: {}", result.halt));
+    }
+    if let Some(gas) = test.gas {
+        if U256::from(result.gas_left) != gas {
+            return Some(format!(
+                "gas left mismatch: expect {}, got {}",
+                gas, result.gas_left
+            ));
+        }
+    }
+    if let Some(ref out) = test.out {
+        if hex::decode(out).unwrap() != result.output {
+            return Some(format!(
+                "output mismatch: expect {}, got {}",
+                out,
+                hex::encode(&result.output)
+            ));
+        }
+    }
+    if let Some(logs) = test.logs {
+        let got = logs_hash(&result.logs);
+        if got != logs {
+            return Some(format!("logs hash mismatch: expect {}, got {}", logs, got));
+        }
+    }
+    for address in post.accounts().keys() {
+        let expected = storage(post, address);
+        let got = storage(host.state(), address);
+        if got != expected {
+            return Some(format!(
+                "storage mismatch of {}: expect {:?}, got {:?}",
+                address, expected, got
+            ));
+        }
+    }
+    None
+}
+
+/// Record the result of a test.
+fn run_named_test(name: &str, test: &VmTest, summary: &mut Summary) {
+    match run_test(test) {
+        Some(reason) => summary.failures.push(format!("{}: {}", name, reason)),
+        None => summary.passed += 1,
+    }
+}
+
+fn run_vm_tests(dir: &Path) -> Summary {
+    run_dir(dir, "VM_TESTS_FILTER", run_named_test)
+}
+
+#[test]
+fn vendored_vm_tests() {
+    check(
+        "VM tests",
+        &run_vm_tests(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/VMTests")),
+    );
+}
+
+#[test]
+#[ignore]
+fn ethereum_vm_tests() {
+    let dir = env::var_os("ETHEREUM_TESTS").expect("ETHEREUM_TESTS is not set");
+    check(
+        "VM tests",
+        &run_vm_tests(&Path::new(&dir).join("LegacyTests/Constantinople/VMTests")),
+    );
+}